@@ -0,0 +1,339 @@
+/// Camera aliases
+///
+/// An alias is a short name for one camera, used as `--camera @name`. It records the
+/// camera's device path together with its USB IDs and serial number. The path is tried
+/// first; when Windows regenerates the path (e.g. after the camera moves to another USB
+/// port), a camera with the same vendor ID, product ID and serial number is used instead.
+/// Windows only puts the serial number in the path of single-function USB devices; most
+/// webcams are composite devices (video plus audio), whose interface paths carry a generated
+/// instance ID instead, so their aliases can only be found by path.
+use crate::config::{Config, Scope};
+use crate::device_path::{self, DevicePath};
+use crate::webcam::DeviceInfo;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tracing::instrument;
+
+/// What an alias points at
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CameraAlias {
+    /// Friendly name of the camera when the alias was created, for display only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub camera_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_path: Option<String>,
+    #[serde(
+        default,
+        serialize_with = "device_path::serialize_hex_id",
+        deserialize_with = "device_path::deserialize_hex_id",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub vendor_id: Option<u16>,
+    #[serde(
+        default,
+        serialize_with = "device_path::serialize_hex_id",
+        deserialize_with = "device_path::deserialize_hex_id",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub product_id: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub serial: Option<String>,
+}
+
+impl CameraAlias {
+    /// Alias for a device, recording its path and the IDs parsed from it
+    pub fn new(device: &DeviceInfo) -> Self {
+        let parsed = device
+            .device_path
+            .as_deref()
+            .map(DevicePath::parse)
+            .unwrap_or_default();
+        Self {
+            camera_name: device.name.clone(),
+            device_path: device.device_path.clone(),
+            vendor_id: parsed.vendor_id,
+            product_id: parsed.product_id,
+            serial: parsed.serial,
+        }
+    }
+
+    /// Whether a device path is the one the alias recorded (case-insensitive)
+    pub fn matches_path(&self, path: Option<&str>) -> bool {
+        self.device_path
+            .as_deref()
+            .zip(path)
+            .is_some_and(|(alias, path)| alias.eq_ignore_ascii_case(path))
+    }
+
+    /// Whether a device path carries the vendor ID, product ID and serial number the alias
+    /// recorded. Aliases of cameras without a serial number never match this way.
+    pub fn matches_hardware(&self, path: Option<&str>) -> bool {
+        let (Some(vendor_id), Some(product_id), Some(serial)) =
+            (self.vendor_id, self.product_id, &self.serial)
+        else {
+            return false;
+        };
+        path.map(DevicePath::parse).is_some_and(|parsed| {
+            parsed.vendor_id == Some(vendor_id)
+                && parsed.product_id == Some(product_id)
+                && parsed
+                    .serial
+                    .is_some_and(|s| s.eq_ignore_ascii_case(serial))
+        })
+    }
+
+    /// Indices of the devices the alias resolves to: those with the recorded device path,
+    /// or failing that, those with the recorded USB IDs and serial number
+    pub fn resolve<'a>(&self, paths: impl IntoIterator<Item = Option<&'a str>>) -> Vec<usize> {
+        let paths: Vec<Option<&str>> = paths.into_iter().collect();
+        let find = |matches: &dyn Fn(Option<&str>) -> bool| -> Vec<usize> {
+            paths
+                .iter()
+                .enumerate()
+                .filter(|&(_, &path)| matches(path))
+                .map(|(index, _)| index)
+                .collect()
+        };
+        let by_path = find(&|path| self.matches_path(path));
+        if by_path.is_empty() {
+            find(&|path| self.matches_hardware(path))
+        } else {
+            by_path
+        }
+    }
+
+    /// Short description of what the alias points at
+    pub fn target(&self) -> String {
+        match (&self.device_path, self.vendor_id.zip(self.product_id)) {
+            (_, Some((vid, pid))) => {
+                let mut target = format!("vidpid:{:04x}:{:04x}", vid, pid);
+                if let Some(ref serial) = self.serial {
+                    target.push_str(&format!(", serial:{}", serial));
+                }
+                target
+            }
+            (Some(path), None) => format!("path:{}", path),
+            (None, None) => "nothing".to_string(),
+        }
+    }
+}
+
+/// Aliases from the machine and user config files. A user alias hides a machine alias of
+/// the same name.
+#[derive(Debug, Default)]
+pub struct Aliases {
+    entries: BTreeMap<String, (Scope, CameraAlias)>,
+}
+
+impl Aliases {
+    /// Load aliases from both config files
+    #[instrument]
+    pub fn load() -> Result<Self> {
+        Ok(Self::merge([
+            (Scope::Machine, Config::load(Scope::Machine)?),
+            (Scope::User, Config::load(Scope::User)?),
+        ]))
+    }
+
+    // Combine config files, later ones hiding aliases of the same name in earlier ones
+    fn merge(configs: impl IntoIterator<Item = (Scope, Config)>) -> Self {
+        let mut entries = BTreeMap::new();
+        for (scope, config) in configs {
+            for (name, alias) in config.aliases {
+                entries.insert(name.to_lowercase(), (scope, alias));
+            }
+        }
+        Self { entries }
+    }
+
+    /// Look up an alias by name (case-insensitive)
+    pub fn get(&self, name: &str) -> Option<&CameraAlias> {
+        self.entries
+            .get(&name.to_lowercase())
+            .map(|(_, alias)| alias)
+    }
+
+    /// Every alias with the config file it came from, sorted by name
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Scope, &CameraAlias)> {
+        self.entries
+            .iter()
+            .map(|(name, (scope, alias))| (name.as_str(), *scope, alias))
+    }
+
+    /// Names of the aliases resolving to the device at `index`
+    pub fn names_for(&self, paths: &[Option<&str>], index: usize) -> Vec<&str> {
+        self.iter()
+            .filter(|(_, _, alias)| alias.resolve(paths.iter().copied()).contains(&index))
+            .map(|(name, _, _)| name)
+            .collect()
+    }
+}
+
+/// Add an alias to a config file. Fails if the file already has an alias of that name.
+#[instrument(skip(alias))]
+pub fn add(scope: Scope, name: &str, alias: CameraAlias) -> Result<()> {
+    validate_name(name)?;
+    let mut config = Config::load(scope)?;
+    insert(&mut config, scope, name, alias)?;
+    config.save(scope)
+}
+
+// Add an alias to a loaded config file, unless one of that name is already there
+fn insert(config: &mut Config, scope: Scope, name: &str, alias: CameraAlias) -> Result<()> {
+    if config.aliases.keys().any(|n| n.eq_ignore_ascii_case(name)) {
+        anyhow::bail!(
+            "Alias '{}' already exists in the {} config; remove it first",
+            name,
+            scope
+        );
+    }
+    config.aliases.insert(name.to_lowercase(), alias);
+    Ok(())
+}
+
+/// Remove an alias from a config file. Returns false if it wasn't there.
+#[instrument]
+pub fn remove(scope: Scope, name: &str) -> Result<bool> {
+    let mut config = Config::load(scope)?;
+    if !delete(&mut config, name) {
+        return Ok(false);
+    }
+    config.save(scope)?;
+    Ok(true)
+}
+
+// Remove an alias from a loaded config file. Returns false if it wasn't there.
+fn delete(config: &mut Config, name: &str) -> bool {
+    let before = config.aliases.len();
+    config.aliases.retain(|n, _| !n.eq_ignore_ascii_case(name));
+    config.aliases.len() != before
+}
+
+/// Check an alias name: letters, digits, '-' and '_', starting with a letter
+pub fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.len() > 64 {
+        anyhow::bail!("Alias name must be 1 to 64 characters long");
+    }
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic())
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        anyhow::bail!(
+            "Invalid alias name '{}': use letters, digits, '-' and '_', starting with a letter",
+            name
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A single-function USB camera, whose instance ID is its serial number
+    const KIYO: &str =
+        r"\\?\usb#vid_1532&pid_0e05#A1B2C3D4#{65e8773d-8f56-11d0-a3b9-00a0c9223196}\global";
+    // The same camera after Windows gave it a new path
+    const KIYO_MOVED: &str =
+        r"\\?\USB#VID_1532&PID_0E05#a1b2c3d4#{65e8773d-8f56-11d0-a3b9-00a0c9223196}\x";
+    // A composite camera: the interface's instance ID is generated, so there's no serial
+    const C920: &str = r"\\?\usb#vid_046d&pid_082d&mi_00#7&1c3f2a4b&0&0000#{65e8773d-8f56-11d0-a3b9-00a0c9223196}\global";
+
+    fn alias(path: &str) -> CameraAlias {
+        CameraAlias::new(&DeviceInfo::named("Camera").with_path(path))
+    }
+
+    #[test]
+    fn new_records_ids_from_the_path() {
+        let kiyo = alias(KIYO);
+        assert_eq!(
+            (kiyo.vendor_id, kiyo.product_id, kiyo.serial.as_deref()),
+            (Some(0x1532), Some(0x0e05), Some("A1B2C3D4"))
+        );
+        assert_eq!(kiyo.target(), "vidpid:1532:0e05, serial:A1B2C3D4");
+
+        let c920 = alias(C920);
+        assert_eq!(c920.serial, None);
+        assert_eq!(c920.target(), "vidpid:046d:082d");
+        assert_eq!(alias("/dev/video0").target(), "path:/dev/video0");
+    }
+
+    #[test]
+    fn hardware_match_needs_ids_and_serial() {
+        let kiyo = alias(KIYO);
+        assert!(kiyo.matches_hardware(Some(KIYO_MOVED)));
+        assert!(!kiyo.matches_hardware(Some(C920)));
+        assert!(!kiyo.matches_hardware(None));
+        // Same model, another unit
+        assert!(!kiyo.matches_hardware(Some(&KIYO.replace("A1B2C3D4", "E5F6A7B8"))));
+
+        // Without a serial number, only the path identifies the camera
+        let c920 = alias(C920);
+        assert!(!c920.matches_hardware(Some(C920)));
+        assert!(c920.matches_path(Some(&C920.to_uppercase())));
+    }
+
+    #[test]
+    fn resolve_prefers_the_path() {
+        let kiyo = alias(KIYO);
+        assert_eq!(
+            kiyo.resolve([Some(C920), Some(KIYO_MOVED), Some(KIYO)]),
+            [2]
+        );
+        // Falls back to the serial number once the path is gone
+        assert_eq!(kiyo.resolve([Some(C920), None, Some(KIYO_MOVED)]), [2]);
+        assert!(alias(C920).resolve([Some(KIYO), None]).is_empty());
+    }
+
+    #[test]
+    fn alias_names() {
+        for name in ["podium", "Room-1", "a_b"] {
+            assert!(validate_name(name).is_ok(), "{}", name);
+        }
+        for name in ["", "1room", "-x", "has space", "@podium", &"a".repeat(65)] {
+            assert!(validate_name(name).is_err(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn insert_and_delete_ignore_case() {
+        let mut config = Config::default();
+        insert(&mut config, Scope::User, "Podium", alias(KIYO)).unwrap();
+        assert!(config.aliases.contains_key("podium"));
+        let err = insert(&mut config, Scope::User, "PODIUM", alias(C920)).unwrap_err();
+        assert!(err.to_string().contains("already exists"));
+
+        assert!(!delete(&mut config, "desk"));
+        assert!(delete(&mut config, "PoDiUm"));
+        assert!(config.aliases.is_empty());
+    }
+
+    #[test]
+    fn user_aliases_hide_machine_aliases() {
+        let mut machine = Config::default();
+        machine.aliases.insert("podium".into(), alias(KIYO));
+        machine.aliases.insert("desk".into(), alias(C920));
+        let mut user = Config::default();
+        user.aliases.insert("Podium".into(), alias(C920));
+
+        let aliases = Aliases::merge([(Scope::Machine, machine), (Scope::User, user)]);
+        let listed: Vec<_> = aliases
+            .iter()
+            .map(|(name, scope, alias)| (name, scope, alias.serial.is_some()))
+            .collect();
+        assert_eq!(
+            listed,
+            [
+                ("desk", Scope::Machine, false),
+                ("podium", Scope::User, false)
+            ]
+        );
+        assert_eq!(aliases.get("PODIUM"), Some(&alias(C920)));
+        assert_eq!(
+            aliases.names_for(&[Some(KIYO), Some(C920)], 1),
+            ["desk", "podium"]
+        );
+    }
+}
@@ -0,0 +1,86 @@
+/// Camera backend abstraction
+///
+/// A backend is the layer that actually talks to camera hardware (or something pretending
+/// to be camera hardware). The domain logic in `webcam` only ever goes through the
+/// `CameraBackend` trait, so the CLI can run against any implementation. The calls mirror
+/// the DirectShow `IAMVideoProcAmp`/`IAMCameraControl` interfaces: enumerate devices, then
+/// GetRange/Get/Set individual properties by interface and numeric ID.
+#[cfg(windows)]
+pub mod directshow;
+pub mod ordered;
+pub mod record;
+pub mod simulated;
+#[cfg(target_os = "linux")]
+pub mod v4l2;
+
+use crate::webcam::{DeviceInfo, PropertyType};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// Capability range of a single property, as reported by GetRange
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PropertyRange {
+    pub min: i32,
+    pub max: i32,
+    pub step: i32,
+    pub default: i32,
+    pub caps: i32,
+}
+
+/// Access to video capture devices and their properties
+pub trait CameraBackend {
+    /// Enumerate video capture devices. Only `name` and `device_path` are filled in;
+    /// property lists are populated by `webcam::enumerate_devices` via `get_range`/`get`.
+    fn enumerate(&self) -> Result<Vec<DeviceInfo>>;
+
+    /// Read the capability range of a property. An error means the property is not supported.
+    fn get_range(
+        &self,
+        device: &DeviceInfo,
+        property_type: PropertyType,
+        property_id: i32,
+    ) -> Result<PropertyRange>;
+
+    /// Read the current value and flags of a property, returned as `(value, flags)`
+    fn get(
+        &self,
+        device: &DeviceInfo,
+        property_type: PropertyType,
+        property_id: i32,
+    ) -> Result<(i32, i32)>;
+
+    /// Write a property value, either in Auto or Manual mode
+    fn set(
+        &self,
+        device: &DeviceInfo,
+        property_type: PropertyType,
+        property_id: i32,
+        value: i32,
+        auto: bool,
+    ) -> Result<()>;
+
+    /// Whether a write has taken effect by the time `set` returns, so reading the property
+    /// back needs no settle delay. Real drivers can apply settings late; simulated and
+    /// replayed cameras don't.
+    fn applies_writes_immediately(&self) -> bool {
+        false
+    }
+}
+
+/// Open the native backend for the current platform
+pub fn default_backend() -> Result<Box<dyn CameraBackend>> {
+    #[cfg(windows)]
+    {
+        Ok(Box::new(directshow::DirectShowBackend::new()?))
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        Ok(Box::new(v4l2::V4l2Backend::new()))
+    }
+
+    #[cfg(not(any(windows, target_os = "linux")))]
+    {
+        anyhow::bail!("No camera backend is available on this platform")
+    }
+}
@@ -0,0 +1,386 @@
+/// DirectShow camera backend
+///
+/// This module handles all DirectShow COM interactions: device enumeration through the
+/// system device enumerator, and property access through `IAMVideoProcAmp` and
+/// `IAMCameraControl`. Bound device filters are cached per device path so that reading
+/// every property of a device only walks the moniker list once.
+use super::{CameraBackend, PropertyRange};
+use crate::webcam::{DeviceInfo, PropertyType};
+use anyhow::{Context, Result};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use tracing::{debug, instrument, trace};
+use windows::{
+    Win32::Foundation::*, Win32::Media::DirectShow::*,
+    Win32::System::Com::StructuredStorage::IPropertyBag, Win32::System::Com::*, core::*,
+};
+
+/// RAII guard for COM initialization/cleanup
+///
+/// COM interfaces (ICreateDevEnum, IEnumMoniker, IMoniker, IPropertyBag, etc.)
+/// are automatically cleaned up when they go out of scope via their Drop implementations
+/// in the windows-rs crate. This guard only handles CoInitialize/CoUninitialize.
+struct ComGuard;
+
+impl ComGuard {
+    unsafe fn new() -> Result<Self> {
+        unsafe {
+            let hr = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+            // S_OK (0) = initialized, S_FALSE (1) = already initialized
+            // Both are considered success for our purposes
+            if hr.is_err() {
+                return Err(anyhow::anyhow!("Failed to initialize COM: {:?}", hr));
+            }
+        }
+        Ok(ComGuard)
+    }
+}
+
+impl Drop for ComGuard {
+    fn drop(&mut self) {
+        unsafe {
+            CoUninitialize();
+        }
+    }
+}
+
+// DirectShow GUIDs for device enumeration
+const CLSID_SYSTEM_DEVICE_ENUM: GUID = GUID::from_u128(0x62be5d10_60eb_11d0_bd3b_00a0c911ce86);
+const CLSID_VIDEO_INPUT_DEVICE_CATEGORY: GUID =
+    GUID::from_u128(0x860bb310_5d01_11d0_bd3b_00a0c911ce86);
+
+/// Backend that talks to cameras through DirectShow
+///
+/// COM stays initialized for the lifetime of the backend. Field order matters: the cached
+/// filters must be released before the `ComGuard` uninitializes COM.
+pub struct DirectShowBackend {
+    filters: RefCell<HashMap<String, IBaseFilter>>,
+    _com: ComGuard,
+}
+
+impl DirectShowBackend {
+    pub fn new() -> Result<Self> {
+        debug!("Initializing COM");
+        let com = unsafe { ComGuard::new()? };
+        debug!("COM initialized successfully");
+        Ok(Self {
+            filters: RefCell::new(HashMap::new()),
+            _com: com,
+        })
+    }
+
+    /// Return the bound IBaseFilter for a device, binding and caching it on first use
+    fn filter(&self, device: &DeviceInfo) -> Result<IBaseFilter> {
+        let target_path = device
+            .device_path
+            .as_ref()
+            .context("Device path not available")?;
+
+        if let Some(filter) = self.filters.borrow().get(target_path) {
+            return Ok(filter.clone());
+        }
+
+        let mon = unsafe { find_device_by_path(target_path)? };
+        let filter: IBaseFilter =
+            unsafe { mon.BindToObject(None, None) }.context("Failed to bind to device filter")?;
+        self.filters
+            .borrow_mut()
+            .insert(target_path.clone(), filter.clone());
+        Ok(filter)
+    }
+}
+
+impl CameraBackend for DirectShowBackend {
+    #[instrument(skip(self))]
+    fn enumerate(&self) -> Result<Vec<DeviceInfo>> {
+        let Some(enum_moniker) = (unsafe { create_video_input_enumerator()? }) else {
+            debug!("No video devices found (enum_moniker is None)");
+            return Ok(Vec::new());
+        };
+
+        let mut devices = Vec::new();
+
+        debug!("Starting device enumeration loop");
+        loop {
+            let mut monikers: [Option<IMoniker>; 1] = [None];
+            let mut fetched = 0u32;
+
+            trace!("Calling enum_moniker.Next()");
+            let hr = unsafe { enum_moniker.Next(&mut monikers, Some(&mut fetched)) };
+
+            if hr != S_OK || fetched == 0 {
+                debug!("Enumeration complete. HR: {:?}, Fetched: {}", hr, fetched);
+                break;
+            }
+
+            trace!("Processing moniker {} (fetched={})", devices.len(), fetched);
+
+            if let Some(mon) = &monikers[0] {
+                trace!("Binding moniker to property bag");
+                let device_name = get_device_name(mon).ok();
+                debug!(device_name = ?device_name, "Processing device");
+
+                // Get device path
+                trace!("Getting device path");
+                let device_path = get_device_path(mon).ok();
+
+                devices.push(DeviceInfo {
+                    name: device_name,
+                    device_path,
+                    location: None,
+                    video_proc_amp_properties: Vec::new(),
+                    camera_control_properties: Vec::new(),
+                });
+            }
+        }
+
+        Ok(devices)
+    }
+
+    fn get_range(
+        &self,
+        device: &DeviceInfo,
+        property_type: PropertyType,
+        property_id: i32,
+    ) -> Result<PropertyRange> {
+        let filter = self.filter(device)?;
+        let mut min = 0;
+        let mut max = 0;
+        let mut step = 0;
+        let mut default = 0;
+        let mut caps = 0;
+
+        match property_type {
+            PropertyType::VideoProcAmp => {
+                let iface: IAMVideoProcAmp = filter
+                    .cast()
+                    .context("Failed to get IAMVideoProcAmp interface")?;
+                unsafe {
+                    iface.GetRange(
+                        property_id,
+                        &mut min,
+                        &mut max,
+                        &mut step,
+                        &mut default,
+                        &mut caps,
+                    )
+                }
+            }
+            PropertyType::CameraControl => {
+                let iface: IAMCameraControl = filter
+                    .cast()
+                    .context("Failed to get IAMCameraControl interface")?;
+                unsafe {
+                    iface.GetRange(
+                        property_id,
+                        &mut min,
+                        &mut max,
+                        &mut step,
+                        &mut default,
+                        &mut caps,
+                    )
+                }
+            }
+        }
+        .with_context(|| {
+            format!(
+                "GetRange failed for {} property {}",
+                property_type, property_id
+            )
+        })?;
+
+        Ok(PropertyRange {
+            min,
+            max,
+            step,
+            default,
+            caps,
+        })
+    }
+
+    fn get(
+        &self,
+        device: &DeviceInfo,
+        property_type: PropertyType,
+        property_id: i32,
+    ) -> Result<(i32, i32)> {
+        let filter = self.filter(device)?;
+        let mut value = 0;
+        let mut flags = 0;
+
+        match property_type {
+            PropertyType::VideoProcAmp => {
+                let iface: IAMVideoProcAmp = filter
+                    .cast()
+                    .context("Failed to get IAMVideoProcAmp interface")?;
+                unsafe { iface.Get(property_id, &mut value, &mut flags) }
+            }
+            PropertyType::CameraControl => {
+                let iface: IAMCameraControl = filter
+                    .cast()
+                    .context("Failed to get IAMCameraControl interface")?;
+                unsafe { iface.Get(property_id, &mut value, &mut flags) }
+            }
+        }
+        .with_context(|| format!("Get failed for {} property {}", property_type, property_id))?;
+
+        Ok((value, flags))
+    }
+
+    fn set(
+        &self,
+        device: &DeviceInfo,
+        property_type: PropertyType,
+        property_id: i32,
+        value: i32,
+        auto: bool,
+    ) -> Result<()> {
+        let filter = self.filter(device)?;
+
+        match property_type {
+            PropertyType::VideoProcAmp => {
+                let iface: IAMVideoProcAmp = filter
+                    .cast()
+                    .context("Failed to get VideoProcAmp interface")?;
+                let flags = if auto {
+                    VideoProcAmp_Flags_Auto.0
+                } else {
+                    VideoProcAmp_Flags_Manual.0
+                };
+                unsafe { iface.Set(property_id, value, flags) }
+            }
+            PropertyType::CameraControl => {
+                let iface: IAMCameraControl = filter
+                    .cast()
+                    .context("Failed to get CameraControl interface")?;
+                let flags = if auto {
+                    CameraControl_Flags_Auto.0
+                } else {
+                    CameraControl_Flags_Manual.0
+                };
+                unsafe { iface.Set(property_id, value, flags) }
+            }
+        }
+        .map_err(Into::into)
+    }
+}
+
+/// Create a moniker enumerator over the video input device category.
+/// Returns None when the category is empty.
+unsafe fn create_video_input_enumerator() -> Result<Option<IEnumMoniker>> {
+    unsafe {
+        debug!("Creating ICreateDevEnum");
+        // Create the System Device Enumerator
+        let dev_enum: ICreateDevEnum =
+            CoCreateInstance(&CLSID_SYSTEM_DEVICE_ENUM, None, CLSCTX_INPROC_SERVER)
+                .context("Failed to create device enumerator")?;
+        debug!("ICreateDevEnum created successfully");
+
+        debug!("Creating class enumerator for video input devices");
+        // Create an enumerator for the video input device category
+        let mut enum_moniker: Option<IEnumMoniker> = None;
+        let hr = dev_enum.CreateClassEnumerator(
+            &CLSID_VIDEO_INPUT_DEVICE_CATEGORY,
+            &mut enum_moniker,
+            0,
+        );
+        debug!("CreateClassEnumerator returned: {:?}", hr);
+        hr.context("Failed to create video device class enumerator")?;
+
+        Ok(enum_moniker)
+    }
+}
+
+#[instrument(skip(moniker))]
+fn get_device_name(moniker: &IMoniker) -> Result<String> {
+    debug!("Reading FriendlyName from property bag");
+    let result = get_property_string(moniker, "FriendlyName");
+    if let Ok(ref name) = result {
+        debug!(friendly_name = %name, "Device name obtained");
+    } else {
+        trace!("Failed to read FriendlyName");
+    }
+    result
+}
+
+#[instrument(skip(moniker))]
+fn get_device_path(moniker: &IMoniker) -> Result<String> {
+    debug!("Reading DevicePath from property bag");
+    let result = get_property_string(moniker, "DevicePath");
+    if let Ok(ref path) = result {
+        trace!(device_path = %path, "Device path obtained");
+    } else {
+        trace!("Failed to read DevicePath");
+    }
+    result
+}
+
+#[instrument(skip(moniker))]
+fn get_property_string(moniker: &IMoniker, prop_name: &str) -> Result<String> {
+    trace!(property_name = %prop_name, "Binding moniker to property bag");
+    use windows::Win32::System::Variant::{VARIANT, VT_BSTR, VariantClear};
+    use windows::core::HSTRING;
+
+    // Bind to property bag (safe)
+    let prop_bag: IPropertyBag =
+        unsafe { moniker.BindToStorage(None, None) }.with_context(|| {
+            format!(
+                "Failed to bind to property bag for property '{}'",
+                prop_name
+            )
+        })?;
+    trace!("BindToStorage successful");
+
+    let mut var = VARIANT::default();
+    let prop_name_hstr = HSTRING::from(prop_name);
+
+    // Read property value (unsafe only for FFI call)
+    unsafe { prop_bag.Read(PCWSTR(prop_name_hstr.as_ptr()), &mut var, None) }
+        .with_context(|| format!("Failed to read property '{}'", prop_name))?;
+
+    // Extract the value (safe)
+    let result = if unsafe { var.Anonymous.Anonymous.vt } == VT_BSTR {
+        // Use windows::core::BSTR for conversion
+        let bstr = unsafe { &var.Anonymous.Anonymous.Anonymous.bstrVal };
+        let value = bstr.to_string();
+        trace!(property_value = %value, "Property value retrieved");
+        Ok(value)
+    } else {
+        Err(anyhow::anyhow!("Property '{}' is not a BSTR", prop_name))
+    };
+
+    // Always clear VARIANT regardless of success or failure (unsafe only for FFI call)
+    let _ = unsafe { VariantClear(&mut var) };
+
+    result
+}
+
+/// Find a device moniker by its DirectShow device path
+/// Used to locate the device filter for property access
+unsafe fn find_device_by_path(target_path: &str) -> Result<IMoniker> {
+    unsafe {
+        let Some(enum_moniker) = create_video_input_enumerator()? else {
+            anyhow::bail!("No video devices found");
+        };
+
+        loop {
+            let mut monikers: [Option<IMoniker>; 1] = [None];
+            let mut fetched = 0u32;
+
+            let hr = enum_moniker.Next(&mut monikers, Some(&mut fetched));
+
+            if hr != S_OK || fetched == 0 {
+                break;
+            }
+
+            if let Some(mon) = monikers[0].take()
+                && let Ok(path) = get_device_path(&mon)
+                && path == target_path
+            {
+                return Ok(mon);
+            }
+        }
+
+        anyhow::bail!("Device not found")
+    }
+}
@@ -0,0 +1,277 @@
+/// Deterministic device ordering
+///
+/// DirectShow enumerates cameras in whatever order the system device enumerator returns,
+/// which can change between reboots, and camera indices follow that order.
+/// `OrderedBackend` wraps another backend and sorts the devices it enumerates, either by
+/// device path or by the order they were first seen on this machine. The first-seen order
+/// is kept in `devices.json` next to the user config file, so indices stay stable across
+/// runs. Callers opt in to that file with `first_seen_file`, so simulated cameras never see
+/// or change it and read-only commands leave it alone.
+use super::{CameraBackend, PropertyRange};
+use crate::config::{self, Scope};
+use crate::webcam::{DeviceInfo, PropertyType};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tracing::{debug, instrument, warn};
+
+const SEEN_DEVICES_VERSION: u32 = 1;
+
+// Most devices kept in the first-seen order. Beyond this, the oldest devices that aren't
+// connected are forgotten.
+const MAX_SEEN_DEVICES: usize = 64;
+
+/// How enumerated devices are ordered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Order {
+    /// The order the system enumerates devices in
+    #[default]
+    Enum,
+    /// Sorted by device path (case-insensitive); devices without a path come last
+    Path,
+    /// The order devices were first seen in; new devices are added at the end
+    FirstSeen,
+}
+
+/// How `Order::FirstSeen` uses the first-seen order file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SeenFile {
+    /// Leave the file alone: devices are ordered as they are enumerated in this run. For
+    /// simulated and replayed cameras, so their order doesn't depend on the machine.
+    #[default]
+    Ignore,
+    /// Order devices by the file, without adding new devices to it
+    Read,
+    /// Order devices by the file and add new devices to it. For real hardware only.
+    Update,
+}
+
+/// Backend wrapper that puts enumerated devices in a stable order
+pub struct OrderedBackend<'a> {
+    inner: &'a dyn CameraBackend,
+    order: Order,
+    seen_file: SeenFile,
+}
+
+impl<'a> OrderedBackend<'a> {
+    pub fn new(inner: &'a dyn CameraBackend, order: Order) -> Self {
+        Self {
+            inner,
+            order,
+            seen_file: SeenFile::default(),
+        }
+    }
+
+    /// How the first-seen order file is used; it is ignored unless set here
+    pub fn first_seen_file(mut self, seen_file: SeenFile) -> Self {
+        self.seen_file = seen_file;
+        self
+    }
+}
+
+impl CameraBackend for OrderedBackend<'_> {
+    fn enumerate(&self) -> Result<Vec<DeviceInfo>> {
+        let mut devices = self.inner.enumerate()?;
+        match self.order {
+            Order::Enum => {}
+            // Stable sort, so devices without a path keep their enumeration order
+            Order::Path => devices.sort_by_key(|d| (d.device_path.is_none(), canonical_path(d))),
+            Order::FirstSeen => {
+                let mut seen = match self.seen_file {
+                    SeenFile::Ignore => SeenDevices::default(),
+                    SeenFile::Read | SeenFile::Update => SeenDevices::load().unwrap_or_else(|e| {
+                        warn!("Failed to load first-seen device order: {:#}", e);
+                        SeenDevices::default()
+                    }),
+                };
+                if seen.record(&devices)
+                    && self.seen_file == SeenFile::Update
+                    && let Err(e) = seen.save()
+                {
+                    warn!("Failed to save first-seen device order: {:#}", e);
+                }
+                devices.sort_by_key(|d| seen.position(d));
+            }
+        }
+        debug!(order = ?self.order, device_count = devices.len(), "Devices ordered");
+        Ok(devices)
+    }
+
+    fn get_range(
+        &self,
+        device: &DeviceInfo,
+        property_type: PropertyType,
+        property_id: i32,
+    ) -> Result<PropertyRange> {
+        self.inner.get_range(device, property_type, property_id)
+    }
+
+    fn get(
+        &self,
+        device: &DeviceInfo,
+        property_type: PropertyType,
+        property_id: i32,
+    ) -> Result<(i32, i32)> {
+        self.inner.get(device, property_type, property_id)
+    }
+
+    fn set(
+        &self,
+        device: &DeviceInfo,
+        property_type: PropertyType,
+        property_id: i32,
+        value: i32,
+        auto: bool,
+    ) -> Result<()> {
+        self.inner
+            .set(device, property_type, property_id, value, auto)
+    }
+
+    fn applies_writes_immediately(&self) -> bool {
+        self.inner.applies_writes_immediately()
+    }
+}
+
+// Device paths differ only in case between some Windows APIs
+fn canonical_path(device: &DeviceInfo) -> Option<String> {
+    device.device_path.as_deref().map(str::to_lowercase)
+}
+
+// Contents of the first-seen order file: canonical device paths, oldest first
+#[derive(Debug, Serialize, Deserialize)]
+struct SeenDevices {
+    version: u32,
+    devices: Vec<String>,
+}
+
+impl Default for SeenDevices {
+    fn default() -> Self {
+        Self {
+            version: SEEN_DEVICES_VERSION,
+            devices: Vec::new(),
+        }
+    }
+}
+
+impl SeenDevices {
+    fn path() -> Option<PathBuf> {
+        config::dir(Scope::User).map(|dir| dir.join("devices.json"))
+    }
+
+    #[instrument]
+    fn load() -> Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read '{}'", path.display()));
+            }
+        };
+        let seen: Self = serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse '{}'", path.display()))?;
+        if seen.version > SEEN_DEVICES_VERSION {
+            anyhow::bail!(
+                "'{}' is version {}, but this build only understands version {}",
+                path.display(),
+                seen.version,
+                SEEN_DEVICES_VERSION
+            );
+        }
+        Ok(seen)
+    }
+
+    #[instrument(skip(self))]
+    fn save(&self) -> Result<()> {
+        let path = Self::path().context("Cannot locate the user config directory")?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create directory '{}'", dir.display()))?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .context("Failed to serialize first-seen device order")?;
+        std::fs::write(&path, json).with_context(|| format!("Failed to write '{}'", path.display()))
+    }
+
+    // Append devices not seen before, in enumeration order, then forget the oldest
+    // disconnected devices past MAX_SEEN_DEVICES. Returns whether anything changed.
+    fn record(&mut self, devices: &[DeviceInfo]) -> bool {
+        let connected: Vec<String> = devices.iter().filter_map(canonical_path).collect();
+        let mut changed = false;
+        for path in &connected {
+            if !self.devices.contains(path) {
+                debug!(device_path = %path, "First sighting of device");
+                self.devices.push(path.clone());
+                changed = true;
+            }
+        }
+        while self.devices.len() > MAX_SEEN_DEVICES {
+            let Some(oldest) = self.devices.iter().position(|p| !connected.contains(p)) else {
+                break;
+            };
+            debug!(device_path = %self.devices[oldest], "Forgetting device");
+            self.devices.remove(oldest);
+            changed = true;
+        }
+        changed
+    }
+
+    // Sort key for a device: its first-seen position, with devices without a path last
+    fn position(&self, device: &DeviceInfo) -> usize {
+        canonical_path(device)
+            .and_then(|path| self.devices.iter().position(|p| *p == path))
+            .unwrap_or(usize::MAX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(path: &str) -> DeviceInfo {
+        DeviceInfo::named("Camera").with_path(path)
+    }
+
+    #[test]
+    fn new_devices_go_last() {
+        let mut seen = SeenDevices::default();
+        assert!(seen.record(&[device("B"), device("A")]));
+        assert!(!seen.record(&[device("a"), device("b")]));
+        assert!(seen.record(&[device("C"), device("A")]));
+        assert_eq!(seen.devices, ["b", "a", "c"]);
+
+        assert_eq!(seen.position(&device("A")), 1);
+        assert_eq!(seen.position(&device("D")), usize::MAX);
+    }
+
+    #[test]
+    fn oldest_disconnected_devices_are_forgotten() {
+        let mut seen = SeenDevices::default();
+        for i in 0..MAX_SEEN_DEVICES {
+            seen.record(&[device(&i.to_string())]);
+        }
+        assert_eq!(seen.devices.len(), MAX_SEEN_DEVICES);
+
+        // Device 0 is still connected, so device 1 is the oldest to go
+        assert!(seen.record(&[device("0"), device("new")]));
+        assert_eq!(seen.devices.len(), MAX_SEEN_DEVICES);
+        assert_eq!(seen.devices[0], "0");
+        assert_eq!(seen.devices[1], "2");
+        assert_eq!(seen.devices.last().map(String::as_str), Some("new"));
+    }
+
+    #[test]
+    fn ignored_seen_file_keeps_enumeration_order() {
+        let backend = crate::test_support::sample_backend();
+        let ordered = OrderedBackend::new(&backend, Order::FirstSeen);
+        let paths = |devices: Vec<DeviceInfo>| -> Vec<Option<String>> {
+            devices.into_iter().map(|d| d.device_path).collect()
+        };
+        assert_eq!(
+            paths(ordered.enumerate().unwrap()),
+            paths(backend.enumerate().unwrap())
+        );
+    }
+}
@@ -0,0 +1,438 @@
+/// Record-and-replay backends
+///
+/// `RecordingBackend` wraps another backend and captures every enumerate/GetRange/Get/Set
+/// call together with its result, including failures, into a transcript. `ReplayBackend`
+/// plays a transcript back: each call must match the next recorded call, and gets the
+/// recorded result. A recording made on a user's machine can then reproduce their camera's
+/// exact behaviour offline, on any OS.
+use super::{CameraBackend, PropertyRange};
+use crate::webcam::{DeviceInfo, PropertyType};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::path::Path;
+use tracing::{debug, instrument};
+
+const TRANSCRIPT_VERSION: u32 = 1;
+
+/// A single backend call and its outcome. Errors are stored as their message chain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "call")]
+pub enum Call {
+    Enumerate {
+        result: Result<Vec<DeviceInfo>, String>,
+    },
+    GetRange {
+        device_path: Option<String>,
+        property_type: PropertyType,
+        property_id: i32,
+        result: Result<PropertyRange, String>,
+    },
+    Get {
+        device_path: Option<String>,
+        property_type: PropertyType,
+        property_id: i32,
+        result: Result<(i32, i32), String>,
+    },
+    Set {
+        device_path: Option<String>,
+        property_type: PropertyType,
+        property_id: i32,
+        value: i32,
+        auto: bool,
+        result: Result<(), String>,
+    },
+}
+
+impl fmt::Display for Call {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Enumerate { .. } => f.write_str("Enumerate"),
+            Self::GetRange {
+                device_path,
+                property_type,
+                property_id,
+                ..
+            } => write!(
+                f,
+                "GetRange({}, {} {})",
+                device_path.as_deref().unwrap_or("?"),
+                property_type,
+                property_id
+            ),
+            Self::Get {
+                device_path,
+                property_type,
+                property_id,
+                ..
+            } => write!(
+                f,
+                "Get({}, {} {})",
+                device_path.as_deref().unwrap_or("?"),
+                property_type,
+                property_id
+            ),
+            Self::Set {
+                device_path,
+                property_type,
+                property_id,
+                value,
+                auto,
+                ..
+            } => write!(
+                f,
+                "Set({}, {} {}, value={}, auto={})",
+                device_path.as_deref().unwrap_or("?"),
+                property_type,
+                property_id,
+                value,
+                auto
+            ),
+        }
+    }
+}
+
+/// Transcript file contents
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Transcript {
+    pub version: u32,
+    pub calls: Vec<Call>,
+}
+
+// Store a result in its serializable form
+fn capture<T: Clone>(result: &Result<T>) -> Result<T, String> {
+    result.as_ref().cloned().map_err(|e| format!("{:#}", e))
+}
+
+/// Backend wrapper that records every call made through it
+pub struct RecordingBackend {
+    inner: Box<dyn CameraBackend>,
+    calls: RefCell<Vec<Call>>,
+}
+
+impl RecordingBackend {
+    pub fn new(inner: Box<dyn CameraBackend>) -> Self {
+        Self {
+            inner,
+            calls: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Write the calls recorded so far to a transcript file
+    #[instrument(skip(self))]
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let transcript = Transcript {
+            version: TRANSCRIPT_VERSION,
+            calls: self.calls.borrow().clone(),
+        };
+        debug!(call_count = transcript.calls.len(), "Saving transcript");
+        let json =
+            serde_json::to_string_pretty(&transcript).context("Failed to serialize transcript")?;
+        std::fs::write(path, json)
+            .with_context(|| format!("Failed to write transcript '{}'", path.display()))
+    }
+}
+
+impl CameraBackend for RecordingBackend {
+    fn enumerate(&self) -> Result<Vec<DeviceInfo>> {
+        let result = self.inner.enumerate();
+        self.calls.borrow_mut().push(Call::Enumerate {
+            result: capture(&result),
+        });
+        result
+    }
+
+    fn get_range(
+        &self,
+        device: &DeviceInfo,
+        property_type: PropertyType,
+        property_id: i32,
+    ) -> Result<PropertyRange> {
+        let result = self.inner.get_range(device, property_type, property_id);
+        self.calls.borrow_mut().push(Call::GetRange {
+            device_path: device.device_path.clone(),
+            property_type,
+            property_id,
+            result: capture(&result),
+        });
+        result
+    }
+
+    fn get(
+        &self,
+        device: &DeviceInfo,
+        property_type: PropertyType,
+        property_id: i32,
+    ) -> Result<(i32, i32)> {
+        let result = self.inner.get(device, property_type, property_id);
+        self.calls.borrow_mut().push(Call::Get {
+            device_path: device.device_path.clone(),
+            property_type,
+            property_id,
+            result: capture(&result),
+        });
+        result
+    }
+
+    fn set(
+        &self,
+        device: &DeviceInfo,
+        property_type: PropertyType,
+        property_id: i32,
+        value: i32,
+        auto: bool,
+    ) -> Result<()> {
+        let result = self
+            .inner
+            .set(device, property_type, property_id, value, auto);
+        self.calls.borrow_mut().push(Call::Set {
+            device_path: device.device_path.clone(),
+            property_type,
+            property_id,
+            value,
+            auto,
+            result: capture(&result),
+        });
+        result
+    }
+
+    fn applies_writes_immediately(&self) -> bool {
+        self.inner.applies_writes_immediately()
+    }
+}
+
+/// Backend that replays a recorded transcript
+pub struct ReplayBackend {
+    calls: RefCell<VecDeque<Call>>,
+}
+
+impl ReplayBackend {
+    pub fn new(transcript: Transcript) -> Result<Self> {
+        if transcript.version != TRANSCRIPT_VERSION {
+            anyhow::bail!(
+                "Unsupported transcript version {} (expected {})",
+                transcript.version,
+                TRANSCRIPT_VERSION
+            );
+        }
+        Ok(Self {
+            calls: RefCell::new(transcript.calls.into()),
+        })
+    }
+
+    /// Load a transcript file written by `RecordingBackend::save`
+    #[instrument]
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read transcript '{}'", path.display()))?;
+        let transcript: Transcript = serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse transcript '{}'", path.display()))?;
+        debug!(call_count = transcript.calls.len(), "Transcript loaded");
+        Self::new(transcript)
+    }
+
+    // Take the next recorded call, failing if the transcript is exhausted
+    fn next(&self, actual: &dyn fmt::Display) -> Result<Call> {
+        self.calls
+            .borrow_mut()
+            .pop_front()
+            .with_context(|| format!("Replay exhausted: no recorded call for {}", actual))
+    }
+}
+
+// Turn a recorded outcome back into a live result
+fn restore<T>(result: Result<T, String>) -> Result<T> {
+    result.map_err(anyhow::Error::msg)
+}
+
+// Report a mismatch between the recorded and the replayed call
+fn diverged<T>(expected: &Call, actual: &dyn fmt::Display) -> Result<T> {
+    anyhow::bail!("Replay diverged: recorded {}, but got {}", expected, actual)
+}
+
+impl CameraBackend for ReplayBackend {
+    fn enumerate(&self) -> Result<Vec<DeviceInfo>> {
+        match self.next(&"Enumerate")? {
+            Call::Enumerate { result } => restore(result),
+            other => diverged(&other, &"Enumerate"),
+        }
+    }
+
+    fn get_range(
+        &self,
+        device: &DeviceInfo,
+        property_type: PropertyType,
+        property_id: i32,
+    ) -> Result<PropertyRange> {
+        let actual = Call::GetRange {
+            device_path: device.device_path.clone(),
+            property_type,
+            property_id,
+            result: Err(String::new()),
+        };
+        match self.next(&actual)? {
+            Call::GetRange {
+                device_path,
+                property_type: t,
+                property_id: id,
+                result,
+            } if device_path == device.device_path && t == property_type && id == property_id => {
+                restore(result)
+            }
+            other => diverged(&other, &actual),
+        }
+    }
+
+    fn get(
+        &self,
+        device: &DeviceInfo,
+        property_type: PropertyType,
+        property_id: i32,
+    ) -> Result<(i32, i32)> {
+        let actual = Call::Get {
+            device_path: device.device_path.clone(),
+            property_type,
+            property_id,
+            result: Err(String::new()),
+        };
+        match self.next(&actual)? {
+            Call::Get {
+                device_path,
+                property_type: t,
+                property_id: id,
+                result,
+            } if device_path == device.device_path && t == property_type && id == property_id => {
+                restore(result)
+            }
+            other => diverged(&other, &actual),
+        }
+    }
+
+    fn set(
+        &self,
+        device: &DeviceInfo,
+        property_type: PropertyType,
+        property_id: i32,
+        value: i32,
+        auto: bool,
+    ) -> Result<()> {
+        let actual = Call::Set {
+            device_path: device.device_path.clone(),
+            property_type,
+            property_id,
+            value,
+            auto,
+            result: Err(String::new()),
+        };
+        match self.next(&actual)? {
+            Call::Set {
+                device_path,
+                property_type: t,
+                property_id: id,
+                value: v,
+                auto: a,
+                result,
+            } if device_path == device.device_path
+                && t == property_type
+                && id == property_id
+                && v == value
+                && a == auto =>
+            {
+                restore(result)
+            }
+            other => diverged(&other, &actual),
+        }
+    }
+
+    // The transcript already holds what the camera reported after each write
+    fn applies_writes_immediately(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Camera;
+    use crate::test_support::sample_backend;
+    use crate::webcam::PropertyValue;
+
+    // Record a session against the sample fixture and return its transcript, as it would be
+    // read back from a file
+    fn record_session() -> Transcript {
+        let recorder = RecordingBackend::new(Box::new(sample_backend()));
+
+        let mut camera = Camera::open(&recorder, 0).unwrap();
+        camera
+            .set("Brightness", &PropertyValue::Manual(200))
+            .unwrap();
+        // Failures are recorded too
+        assert!(camera.set("Exposure", &PropertyValue::Manual(1)).is_err());
+        assert!(camera.set("Contrast", &PropertyValue::Auto).is_err());
+
+        let transcript = Transcript {
+            version: TRANSCRIPT_VERSION,
+            calls: recorder.calls.borrow().clone(),
+        };
+        let json = serde_json::to_string(&transcript).unwrap();
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn replay_reproduces_recorded_session() {
+        let replay = ReplayBackend::new(record_session()).unwrap();
+
+        let mut camera = Camera::open(&replay, 0).unwrap();
+        assert_eq!(camera.name(), "Logitech HD Pro Webcam C920");
+        assert_eq!(camera.property("Brightness").unwrap().current, Some(128));
+        assert_eq!(
+            camera
+                .set("Brightness", &PropertyValue::Manual(200))
+                .unwrap(),
+            PropertyValue::Manual(200)
+        );
+        // Out of range is caught before it reaches the backend, so it isn't recorded
+        assert!(camera.set("Exposure", &PropertyValue::Manual(1)).is_err());
+        let err = camera.set("Contrast", &PropertyValue::Auto).unwrap_err();
+        assert!(err.to_string().contains("does not support Auto mode"));
+
+        // Nothing is left to replay
+        let err = replay.enumerate().unwrap_err();
+        assert!(err.to_string().contains("Replay exhausted"));
+    }
+
+    #[test]
+    fn replay_rejects_out_of_order_calls() {
+        let replay = ReplayBackend::new(record_session()).unwrap();
+        let device = DeviceInfo::named("OBS Virtual Camera").with_path("simulated#1");
+
+        // The session started with an enumeration, not a read
+        let err = replay
+            .get(&device, PropertyType::VideoProcAmp, 0)
+            .unwrap_err();
+        assert!(err.to_string().contains("Replay diverged"), "{}", err);
+    }
+
+    #[test]
+    fn replay_rejects_different_arguments() {
+        let replay = ReplayBackend::new(record_session()).unwrap();
+        let devices = replay.enumerate().unwrap();
+
+        // The first recorded GetRange is for Brightness (ID 0), not Contrast
+        let err = replay
+            .get_range(&devices[0], PropertyType::VideoProcAmp, 1)
+            .unwrap_err();
+        assert!(err.to_string().contains("Replay diverged"), "{}", err);
+    }
+
+    #[test]
+    fn unsupported_transcript_version_is_rejected() {
+        let transcript = Transcript {
+            version: TRANSCRIPT_VERSION + 1,
+            calls: Vec::new(),
+        };
+        assert!(ReplayBackend::new(transcript).is_err());
+    }
+}
@@ -0,0 +1,180 @@
+/// Simulated camera backend
+///
+/// Cameras are loaded from a JSON fixture holding a list of `DeviceInfo` objects, the same
+/// shape `webcam::enumerate_devices` produces. Writes are validated against each property's
+/// min/max/step and caps the way a driver would, and the written state is kept so later
+/// reads through the same backend see it. Nothing touches real hardware, so this works on
+/// any platform.
+use super::{CameraBackend, PropertyRange};
+use crate::webcam::{self, DeviceInfo, FLAGS_AUTO, FLAGS_MANUAL, PropertyInfo, PropertyType};
+use anyhow::{Context, Result};
+use std::cell::RefCell;
+use std::path::Path;
+use tracing::{debug, instrument};
+
+/// Backend that serves cameras from an in-memory fixture
+pub struct SimulatedBackend {
+    devices: RefCell<Vec<DeviceInfo>>,
+}
+
+impl SimulatedBackend {
+    /// Create a backend from a list of devices. Devices without a device path are given a
+    /// synthetic one, since paths are how the backend identifies devices.
+    pub fn new(mut devices: Vec<DeviceInfo>) -> Self {
+        for (index, device) in devices.iter_mut().enumerate() {
+            device
+                .device_path
+                .get_or_insert_with(|| format!("simulated#{}", index));
+        }
+        Self {
+            devices: RefCell::new(devices),
+        }
+    }
+
+    /// Load a fixture file containing a JSON array of devices
+    #[instrument]
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read fixture file '{}'", path.display()))?;
+        let devices: Vec<DeviceInfo> = serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse fixture file '{}'", path.display()))?;
+        debug!(device_count = devices.len(), "Fixture loaded");
+        Ok(Self::new(devices))
+    }
+
+    // Find a property on a fixture device and run a closure against it
+    fn with_property<R>(
+        &self,
+        device: &DeviceInfo,
+        property_type: PropertyType,
+        property_id: i32,
+        f: impl FnOnce(&mut PropertyInfo) -> Result<R>,
+    ) -> Result<R> {
+        let name = webcam::property_name(property_type, property_id)
+            .with_context(|| format!("Unknown {} property ID {}", property_type, property_id))?;
+
+        let mut devices = self.devices.borrow_mut();
+        let fixture_device = devices
+            .iter_mut()
+            .find(|d| d.device_path.is_some() && d.device_path == device.device_path)
+            .context("Device not found")?;
+
+        let properties = match property_type {
+            PropertyType::VideoProcAmp => &mut fixture_device.video_proc_amp_properties,
+            PropertyType::CameraControl => &mut fixture_device.camera_control_properties,
+        };
+        let property = properties
+            .iter_mut()
+            .find(|p| p.name.eq_ignore_ascii_case(name))
+            .with_context(|| format!("Property {} not supported", name))?;
+
+        f(property)
+    }
+}
+
+impl CameraBackend for SimulatedBackend {
+    fn enumerate(&self) -> Result<Vec<DeviceInfo>> {
+        Ok(self
+            .devices
+            .borrow()
+            .iter()
+            .map(|d| DeviceInfo {
+                name: d.name.clone(),
+                device_path: d.device_path.clone(),
+                location: d.location.clone(),
+                video_proc_amp_properties: Vec::new(),
+                camera_control_properties: Vec::new(),
+            })
+            .collect())
+    }
+
+    fn get_range(
+        &self,
+        device: &DeviceInfo,
+        property_type: PropertyType,
+        property_id: i32,
+    ) -> Result<PropertyRange> {
+        self.with_property(device, property_type, property_id, |p| {
+            let (Some(min), Some(max)) = (p.min, p.max) else {
+                anyhow::bail!("Property {} has no range in fixture", p.name);
+            };
+            Ok(PropertyRange {
+                min,
+                max,
+                step: p.step.unwrap_or(1),
+                default: p.default.unwrap_or(min),
+                caps: p.caps.unwrap_or(FLAGS_MANUAL),
+            })
+        })
+    }
+
+    fn get(
+        &self,
+        device: &DeviceInfo,
+        property_type: PropertyType,
+        property_id: i32,
+    ) -> Result<(i32, i32)> {
+        self.with_property(device, property_type, property_id, |p| {
+            let value = p
+                .current
+                .or(p.default)
+                .with_context(|| format!("Property {} has no current value", p.name))?;
+            Ok((value, p.current_flags.unwrap_or(FLAGS_MANUAL)))
+        })
+    }
+
+    fn set(
+        &self,
+        device: &DeviceInfo,
+        property_type: PropertyType,
+        property_id: i32,
+        value: i32,
+        auto: bool,
+    ) -> Result<()> {
+        self.with_property(device, property_type, property_id, |p| {
+            let caps = p.caps.unwrap_or(FLAGS_MANUAL);
+            if auto {
+                if caps & FLAGS_AUTO == 0 {
+                    anyhow::bail!("Property {} does not support Auto mode", p.name);
+                }
+                p.current_flags = Some(FLAGS_AUTO);
+                return Ok(());
+            }
+
+            if caps & FLAGS_MANUAL == 0 {
+                anyhow::bail!("Property {} does not support Manual mode", p.name);
+            }
+            if let (Some(min), Some(max)) = (p.min, p.max)
+                && (value < min || value > max)
+            {
+                anyhow::bail!(
+                    "Value {} is outside the range [{}, {}] of property {}",
+                    value,
+                    min,
+                    max,
+                    p.name
+                );
+            }
+            if let (Some(min), Some(step)) = (p.min, p.step)
+                && step > 1
+                && (value - min) % step != 0
+            {
+                anyhow::bail!(
+                    "Value {} is not a multiple of step {} from {} for property {}",
+                    value,
+                    step,
+                    min,
+                    p.name
+                );
+            }
+
+            p.current = Some(value);
+            p.current_flags = Some(FLAGS_MANUAL);
+            Ok(())
+        })
+    }
+
+    fn applies_writes_immediately(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,910 @@
+/// Video4Linux2 camera backend
+///
+/// Enumerates `/dev/video*` capture nodes and maps V4L2 controls onto the DirectShow
+/// property model, so `get`/`set` output matches between Windows and Linux. Each mapped
+/// property has a value control (e.g. `V4L2_CID_EXPOSURE_ABSOLUTE`), an optional auto
+/// control that switches it between Auto and Manual (e.g. `V4L2_CID_EXPOSURE_AUTO`), and a
+/// scale that converts V4L2 units into DirectShow units where they differ.
+///
+/// All device access goes through the `V4l2Io` trait, so the mapping logic can be driven
+/// by an in-memory implementation without any devices present.
+use super::{CameraBackend, PropertyRange};
+use crate::registry;
+use crate::webcam::{DeviceInfo, FLAGS_AUTO, FLAGS_MANUAL, PropertyType};
+use anyhow::{Context, Result};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io;
+use std::os::fd::AsRawFd;
+use tracing::{debug, instrument, trace};
+
+// Control IDs from linux/v4l2-controls.h
+const V4L2_CID_BASE: u32 = 0x0098_0900;
+const V4L2_CID_BRIGHTNESS: u32 = V4L2_CID_BASE;
+const V4L2_CID_CONTRAST: u32 = V4L2_CID_BASE + 1;
+const V4L2_CID_SATURATION: u32 = V4L2_CID_BASE + 2;
+const V4L2_CID_HUE: u32 = V4L2_CID_BASE + 3;
+const V4L2_CID_AUTO_WHITE_BALANCE: u32 = V4L2_CID_BASE + 12;
+const V4L2_CID_GAMMA: u32 = V4L2_CID_BASE + 16;
+const V4L2_CID_AUTOGAIN: u32 = V4L2_CID_BASE + 18;
+const V4L2_CID_GAIN: u32 = V4L2_CID_BASE + 19;
+const V4L2_CID_POWER_LINE_FREQUENCY: u32 = V4L2_CID_BASE + 24;
+const V4L2_CID_HUE_AUTO: u32 = V4L2_CID_BASE + 25;
+const V4L2_CID_WHITE_BALANCE_TEMPERATURE: u32 = V4L2_CID_BASE + 26;
+const V4L2_CID_SHARPNESS: u32 = V4L2_CID_BASE + 27;
+const V4L2_CID_BACKLIGHT_COMPENSATION: u32 = V4L2_CID_BASE + 28;
+
+const V4L2_CID_CAMERA_CLASS_BASE: u32 = 0x009a_0900;
+const V4L2_CID_EXPOSURE_AUTO: u32 = V4L2_CID_CAMERA_CLASS_BASE + 1;
+const V4L2_CID_EXPOSURE_ABSOLUTE: u32 = V4L2_CID_CAMERA_CLASS_BASE + 2;
+const V4L2_CID_PAN_ABSOLUTE: u32 = V4L2_CID_CAMERA_CLASS_BASE + 8;
+const V4L2_CID_TILT_ABSOLUTE: u32 = V4L2_CID_CAMERA_CLASS_BASE + 9;
+const V4L2_CID_FOCUS_ABSOLUTE: u32 = V4L2_CID_CAMERA_CLASS_BASE + 10;
+const V4L2_CID_FOCUS_AUTO: u32 = V4L2_CID_CAMERA_CLASS_BASE + 12;
+const V4L2_CID_ZOOM_ABSOLUTE: u32 = V4L2_CID_CAMERA_CLASS_BASE + 13;
+const V4L2_CID_IRIS_ABSOLUTE: u32 = V4L2_CID_CAMERA_CLASS_BASE + 17;
+
+// V4L2_CID_EXPOSURE_AUTO menu values
+const V4L2_EXPOSURE_MANUAL: i32 = 1;
+const V4L2_EXPOSURE_APERTURE_PRIORITY: i32 = 3;
+
+const V4L2_CTRL_FLAG_DISABLED: u32 = 0x0001;
+const V4L2_CAP_VIDEO_CAPTURE: u32 = 0x0000_0001;
+const V4L2_CAP_DEVICE_CAPS: u32 = 0x8000_0000;
+
+/// Conversion between V4L2 control units and DirectShow property units
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scale {
+    /// Same units on both sides
+    Identity,
+    /// V4L2 exposure in 100µs units vs DirectShow log2 seconds
+    Log2Exposure,
+    /// V4L2 arc seconds vs DirectShow degrees
+    ArcSeconds,
+    /// V4L2 hundredths vs DirectShow tenths, e.g. f-stop x100 vs x10
+    Hundredths,
+}
+
+impl Scale {
+    fn to_directshow(self, value: i32) -> i32 {
+        match self {
+            Self::Identity => value,
+            Self::Log2Exposure => (f64::from(value.max(1)) * 0.0001).log2().round() as i32,
+            Self::ArcSeconds => (f64::from(value) / 3600.0).round() as i32,
+            Self::Hundredths => (f64::from(value) / 10.0).round() as i32,
+        }
+    }
+
+    fn to_v4l2(self, value: i32) -> i32 {
+        match self {
+            Self::Identity => value,
+            Self::Log2Exposure => (2f64.powi(value) / 0.0001).round() as i32,
+            Self::ArcSeconds => value.saturating_mul(3600),
+            Self::Hundredths => value.saturating_mul(10),
+        }
+    }
+
+    fn step_to_directshow(self, step: i32) -> i32 {
+        match self {
+            Self::Identity => step,
+            Self::Log2Exposure => 1,
+            Self::ArcSeconds => (step / 3600).max(1),
+            Self::Hundredths => (step / 10).max(1),
+        }
+    }
+}
+
+/// Control that switches a property between Auto and Manual
+#[derive(Debug, Clone, Copy)]
+struct AutoControl {
+    cid: u32,
+    auto_value: i32,
+    manual_value: i32,
+}
+
+/// Mapping of one DirectShow property, by registry name, onto V4L2 controls
+#[derive(Debug, Clone, Copy)]
+struct ControlMapping {
+    property: &'static str,
+    cid: u32,
+    auto: Option<AutoControl>,
+    scale: Scale,
+}
+
+const fn on_off(cid: u32) -> Option<AutoControl> {
+    Some(AutoControl {
+        cid,
+        auto_value: 1,
+        manual_value: 0,
+    })
+}
+
+const fn mapping(
+    property: &'static str,
+    cid: u32,
+    auto: Option<AutoControl>,
+    scale: Scale,
+) -> ControlMapping {
+    ControlMapping {
+        property,
+        cid,
+        auto,
+        scale,
+    }
+}
+
+const CONTROL_MAPPINGS: &[ControlMapping] = &[
+    mapping("Brightness", V4L2_CID_BRIGHTNESS, None, Scale::Identity),
+    mapping("Contrast", V4L2_CID_CONTRAST, None, Scale::Identity),
+    mapping("Saturation", V4L2_CID_SATURATION, None, Scale::Identity),
+    mapping(
+        "Hue",
+        V4L2_CID_HUE,
+        on_off(V4L2_CID_HUE_AUTO),
+        Scale::Identity,
+    ),
+    mapping(
+        "WhiteBalance",
+        V4L2_CID_WHITE_BALANCE_TEMPERATURE,
+        on_off(V4L2_CID_AUTO_WHITE_BALANCE),
+        Scale::Identity,
+    ),
+    mapping("Gamma", V4L2_CID_GAMMA, None, Scale::Identity),
+    mapping("Sharpness", V4L2_CID_SHARPNESS, None, Scale::Identity),
+    mapping(
+        "BacklightCompensation",
+        V4L2_CID_BACKLIGHT_COMPENSATION,
+        None,
+        Scale::Identity,
+    ),
+    mapping(
+        "Gain",
+        V4L2_CID_GAIN,
+        on_off(V4L2_CID_AUTOGAIN),
+        Scale::Identity,
+    ),
+    // Menu values (Disabled, 50Hz, 60Hz, Auto) are identical to DirectShow
+    mapping(
+        "PowerlineFrequency",
+        V4L2_CID_POWER_LINE_FREQUENCY,
+        None,
+        Scale::Identity,
+    ),
+    mapping(
+        "Exposure",
+        V4L2_CID_EXPOSURE_ABSOLUTE,
+        // UVC cameras implement auto exposure as aperture priority
+        Some(AutoControl {
+            cid: V4L2_CID_EXPOSURE_AUTO,
+            auto_value: V4L2_EXPOSURE_APERTURE_PRIORITY,
+            manual_value: V4L2_EXPOSURE_MANUAL,
+        }),
+        Scale::Log2Exposure,
+    ),
+    // UVC defines focus in millimeters and zoom as objective focal length, and both uvcvideo
+    // and the Windows UVC driver pass them through unchanged, so they match without scaling
+    mapping(
+        "Focus",
+        V4L2_CID_FOCUS_ABSOLUTE,
+        on_off(V4L2_CID_FOCUS_AUTO),
+        Scale::Identity,
+    ),
+    mapping("Pan", V4L2_CID_PAN_ABSOLUTE, None, Scale::ArcSeconds),
+    mapping("Tilt", V4L2_CID_TILT_ABSOLUTE, None, Scale::ArcSeconds),
+    mapping("Zoom", V4L2_CID_ZOOM_ABSOLUTE, None, Scale::Identity),
+    // UVC reports the aperture as f-stop x100; DirectShow uses f-stop x10
+    mapping("Iris", V4L2_CID_IRIS_ABSOLUTE, None, Scale::Hundredths),
+];
+
+fn find_mapping(property_type: PropertyType, property_id: i32) -> Result<&'static ControlMapping> {
+    registry::by_id(property_type, property_id)
+        .and_then(|p| CONTROL_MAPPINGS.iter().find(|m| m.property == p.name))
+        .with_context(|| {
+            format!(
+                "{} property {} has no V4L2 equivalent",
+                property_type, property_id
+            )
+        })
+}
+
+/// Result of VIDIOC_QUERYCTRL
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueryCtrl {
+    pub minimum: i32,
+    pub maximum: i32,
+    pub step: i32,
+    pub default_value: i32,
+    pub flags: u32,
+}
+
+/// Result of VIDIOC_QUERYCAP
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capability {
+    pub card: String,
+    /// Where the device is attached, e.g. `usb-0000:00:14.0-1.3`
+    pub bus_info: String,
+    pub device_caps: u32,
+}
+
+/// The V4L2 ioctl surface used by the backend
+pub trait V4l2Io {
+    /// List candidate device nodes (e.g. `/dev/video0`)
+    fn list_nodes(&self) -> io::Result<Vec<String>>;
+    /// VIDIOC_QUERYCAP
+    fn query_cap(&self, node: &str) -> io::Result<Capability>;
+    /// VIDIOC_QUERYCTRL
+    fn query_ctrl(&self, node: &str, cid: u32) -> io::Result<QueryCtrl>;
+    /// VIDIOC_G_CTRL
+    fn get_ctrl(&self, node: &str, cid: u32) -> io::Result<i32>;
+    /// VIDIOC_S_CTRL
+    fn set_ctrl(&self, node: &str, cid: u32, value: i32) -> io::Result<()>;
+}
+
+/// Backend that talks to cameras through V4L2 controls
+pub struct V4l2Backend<I: V4l2Io = DeviceNodes> {
+    io: I,
+}
+
+impl V4l2Backend<DeviceNodes> {
+    pub fn new() -> Self {
+        Self::with_io(DeviceNodes::default())
+    }
+}
+
+impl Default for V4l2Backend<DeviceNodes> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<I: V4l2Io> V4l2Backend<I> {
+    pub fn with_io(io: I) -> Self {
+        Self { io }
+    }
+
+    fn node<'a>(&self, device: &'a DeviceInfo) -> Result<&'a str> {
+        device
+            .device_path
+            .as_deref()
+            .context("Device path not available")
+    }
+
+    // Whether the property's auto control is currently in its Auto state
+    fn is_auto(&self, node: &str, auto: &AutoControl) -> io::Result<bool> {
+        let value = self.io.get_ctrl(node, auto.cid)?;
+        Ok(value != auto.manual_value)
+    }
+
+    // Whether the auto control exists and is usable on this device
+    fn has_auto(&self, node: &str, mapping: &ControlMapping) -> Option<AutoControl> {
+        let auto = mapping.auto?;
+        match self.io.query_ctrl(node, auto.cid) {
+            Ok(q) if q.flags & V4L2_CTRL_FLAG_DISABLED == 0 => Some(auto),
+            _ => None,
+        }
+    }
+}
+
+impl<I: V4l2Io> CameraBackend for V4l2Backend<I> {
+    #[instrument(skip(self))]
+    fn enumerate(&self) -> Result<Vec<DeviceInfo>> {
+        let nodes = self
+            .io
+            .list_nodes()
+            .context("Failed to list V4L2 device nodes")?;
+
+        let mut devices = Vec::new();
+        for node in nodes {
+            let cap = match self.io.query_cap(&node) {
+                Ok(cap) => cap,
+                Err(e) => {
+                    trace!(node = %node, error = %e, "VIDIOC_QUERYCAP failed");
+                    continue;
+                }
+            };
+            // UVC cameras expose a second metadata node per camera; skip anything
+            // that cannot capture video.
+            if cap.device_caps & V4L2_CAP_VIDEO_CAPTURE == 0 {
+                trace!(node = %node, "Not a video capture node");
+                continue;
+            }
+            debug!(node = %node, card = %cap.card, "Found video capture device");
+            devices.push(DeviceInfo {
+                name: Some(cap.card),
+                device_path: Some(node),
+                location: Some(cap.bus_info).filter(|b| !b.is_empty()),
+                video_proc_amp_properties: Vec::new(),
+                camera_control_properties: Vec::new(),
+            });
+        }
+
+        Ok(devices)
+    }
+
+    fn get_range(
+        &self,
+        device: &DeviceInfo,
+        property_type: PropertyType,
+        property_id: i32,
+    ) -> Result<PropertyRange> {
+        let node = self.node(device)?;
+        let mapping = find_mapping(property_type, property_id)?;
+        let q = self
+            .io
+            .query_ctrl(node, mapping.cid)
+            .with_context(|| format!("VIDIOC_QUERYCTRL failed for control {:#x}", mapping.cid))?;
+        if q.flags & V4L2_CTRL_FLAG_DISABLED != 0 {
+            anyhow::bail!("Control {:#x} is disabled", mapping.cid);
+        }
+
+        let mut caps = FLAGS_MANUAL;
+        if self.has_auto(node, mapping).is_some() {
+            caps |= FLAGS_AUTO;
+        }
+
+        let scale = mapping.scale;
+        Ok(PropertyRange {
+            min: scale.to_directshow(q.minimum),
+            max: scale.to_directshow(q.maximum),
+            step: scale.step_to_directshow(q.step),
+            default: scale.to_directshow(q.default_value),
+            caps,
+        })
+    }
+
+    fn get(
+        &self,
+        device: &DeviceInfo,
+        property_type: PropertyType,
+        property_id: i32,
+    ) -> Result<(i32, i32)> {
+        let node = self.node(device)?;
+        let mapping = find_mapping(property_type, property_id)?;
+        let value = self
+            .io
+            .get_ctrl(node, mapping.cid)
+            .with_context(|| format!("VIDIOC_G_CTRL failed for control {:#x}", mapping.cid))?;
+
+        let flags = match self.has_auto(node, mapping) {
+            Some(auto) if self.is_auto(node, &auto)? => FLAGS_AUTO,
+            _ => FLAGS_MANUAL,
+        };
+
+        Ok((mapping.scale.to_directshow(value), flags))
+    }
+
+    fn set(
+        &self,
+        device: &DeviceInfo,
+        property_type: PropertyType,
+        property_id: i32,
+        value: i32,
+        auto: bool,
+    ) -> Result<()> {
+        let node = self.node(device)?;
+        let mapping = find_mapping(property_type, property_id)?;
+
+        if auto {
+            // Same error as the other backends when the device lacks the auto control
+            let auto = self.has_auto(node, mapping).with_context(|| {
+                format!("Property {} does not support Auto mode", mapping.property)
+            })?;
+            return self
+                .io
+                .set_ctrl(node, auto.cid, auto.auto_value)
+                .with_context(|| format!("VIDIOC_S_CTRL failed for control {:#x}", auto.cid));
+        }
+
+        // The value control is read-only while its auto control is engaged
+        if let Some(auto) = self.has_auto(node, mapping) {
+            self.io
+                .set_ctrl(node, auto.cid, auto.manual_value)
+                .with_context(|| format!("VIDIOC_S_CTRL failed for control {:#x}", auto.cid))?;
+        }
+
+        self.io
+            .set_ctrl(node, mapping.cid, mapping.scale.to_v4l2(value))
+            .with_context(|| format!("VIDIOC_S_CTRL failed for control {:#x}", mapping.cid))
+    }
+}
+
+// ioctl request codes, _IOR/_IOWR('V', nr, struct)
+const VIDIOC_QUERYCAP: u64 = 0x8068_5600;
+const VIDIOC_G_CTRL: u64 = 0xc008_561b;
+const VIDIOC_S_CTRL: u64 = 0xc008_561c;
+const VIDIOC_QUERYCTRL: u64 = 0xc044_5624;
+
+// Kernel ABI structs from linux/videodev2.h; unused fields only exist to keep the layout.
+#[repr(C)]
+#[allow(dead_code)]
+struct RawCapability {
+    driver: [u8; 16],
+    card: [u8; 32],
+    bus_info: [u8; 32],
+    version: u32,
+    capabilities: u32,
+    device_caps: u32,
+    reserved: [u32; 3],
+}
+
+#[repr(C)]
+#[allow(dead_code)]
+struct RawQueryCtrl {
+    id: u32,
+    type_: u32,
+    name: [u8; 32],
+    minimum: i32,
+    maximum: i32,
+    step: i32,
+    default_value: i32,
+    flags: u32,
+    reserved: [u32; 2],
+}
+
+#[repr(C)]
+struct RawControl {
+    id: u32,
+    value: i32,
+}
+
+/// Real `/dev/video*` device nodes. Opened nodes are cached for the backend's lifetime.
+#[derive(Default)]
+pub struct DeviceNodes {
+    files: RefCell<HashMap<String, File>>,
+}
+
+impl DeviceNodes {
+    // Run an ioctl against a node, opening it on first use
+    fn ioctl<T>(&self, node: &str, request: u64, arg: &mut T) -> io::Result<()> {
+        let mut files = self.files.borrow_mut();
+        if !files.contains_key(node) {
+            let file = File::options().read(true).write(true).open(node)?;
+            files.insert(node.to_string(), file);
+        }
+        let file = &files[node];
+
+        // SAFETY: `arg` is a live, exclusively borrowed #[repr(C)] struct whose layout
+        // matches what `request` expects, and the fd stays open for the call.
+        let ret = unsafe { libc::ioctl(file.as_raw_fd(), request as _, arg as *mut T) };
+        if ret < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+// Convert a NUL-padded C string field into a String
+fn c_string(bytes: &[u8]) -> String {
+    let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..len]).into_owned()
+}
+
+impl V4l2Io for DeviceNodes {
+    fn list_nodes(&self) -> io::Result<Vec<String>> {
+        let mut nodes: Vec<(u32, String)> = std::fs::read_dir("/dev")?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let name = entry.file_name().into_string().ok()?;
+                let number = name.strip_prefix("video")?.parse().ok()?;
+                Some((number, format!("/dev/{}", name)))
+            })
+            .collect();
+        nodes.sort();
+        Ok(nodes.into_iter().map(|(_, node)| node).collect())
+    }
+
+    fn query_cap(&self, node: &str) -> io::Result<Capability> {
+        let mut cap = RawCapability {
+            driver: [0; 16],
+            card: [0; 32],
+            bus_info: [0; 32],
+            version: 0,
+            capabilities: 0,
+            device_caps: 0,
+            reserved: [0; 3],
+        };
+        self.ioctl(node, VIDIOC_QUERYCAP, &mut cap)?;
+        let device_caps = if cap.capabilities & V4L2_CAP_DEVICE_CAPS != 0 {
+            cap.device_caps
+        } else {
+            cap.capabilities
+        };
+        Ok(Capability {
+            card: c_string(&cap.card),
+            bus_info: c_string(&cap.bus_info),
+            device_caps,
+        })
+    }
+
+    fn query_ctrl(&self, node: &str, cid: u32) -> io::Result<QueryCtrl> {
+        let mut q = RawQueryCtrl {
+            id: cid,
+            type_: 0,
+            name: [0; 32],
+            minimum: 0,
+            maximum: 0,
+            step: 0,
+            default_value: 0,
+            flags: 0,
+            reserved: [0; 2],
+        };
+        self.ioctl(node, VIDIOC_QUERYCTRL, &mut q)?;
+        Ok(QueryCtrl {
+            minimum: q.minimum,
+            maximum: q.maximum,
+            step: q.step,
+            default_value: q.default_value,
+            flags: q.flags,
+        })
+    }
+
+    fn get_ctrl(&self, node: &str, cid: u32) -> io::Result<i32> {
+        let mut ctrl = RawControl { id: cid, value: 0 };
+        self.ioctl(node, VIDIOC_G_CTRL, &mut ctrl)?;
+        Ok(ctrl.value)
+    }
+
+    fn set_ctrl(&self, node: &str, cid: u32, value: i32) -> io::Result<()> {
+        let mut ctrl = RawControl { id: cid, value };
+        self.ioctl(node, VIDIOC_S_CTRL, &mut ctrl)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NODE: &str = "/dev/video0";
+
+    // In-memory V4L2 device: controls by ID, with their range and current value
+    #[derive(Default)]
+    struct FakeIo {
+        nodes: Vec<(String, Capability)>,
+        controls: RefCell<HashMap<u32, (QueryCtrl, i32)>>,
+    }
+
+    impl FakeIo {
+        fn camera() -> Self {
+            Self {
+                nodes: vec![
+                    (
+                        NODE.to_string(),
+                        Capability {
+                            card: "HD Pro Webcam C920".to_string(),
+                            bus_info: "usb-0000:00:14.0-1.3".to_string(),
+                            device_caps: V4L2_CAP_VIDEO_CAPTURE,
+                        },
+                    ),
+                    // UVC metadata node of the same camera
+                    (
+                        "/dev/video1".to_string(),
+                        Capability {
+                            card: "HD Pro Webcam C920".to_string(),
+                            bus_info: "usb-0000:00:14.0-1.3".to_string(),
+                            device_caps: 0x0080_0000,
+                        },
+                    ),
+                ],
+                controls: RefCell::default(),
+            }
+        }
+
+        fn control(self, cid: u32, minimum: i32, maximum: i32, default_value: i32) -> Self {
+            let q = QueryCtrl {
+                minimum,
+                maximum,
+                step: 1,
+                default_value,
+                flags: 0,
+            };
+            self.controls.borrow_mut().insert(cid, (q, default_value));
+            self
+        }
+
+        fn value(&self, cid: u32) -> i32 {
+            self.controls.borrow()[&cid].1
+        }
+    }
+
+    fn not_found() -> io::Error {
+        io::Error::from_raw_os_error(libc::EINVAL)
+    }
+
+    impl V4l2Io for FakeIo {
+        fn list_nodes(&self) -> io::Result<Vec<String>> {
+            Ok(self.nodes.iter().map(|(node, _)| node.clone()).collect())
+        }
+
+        fn query_cap(&self, node: &str) -> io::Result<Capability> {
+            self.nodes
+                .iter()
+                .find(|(n, _)| n == node)
+                .map(|(_, cap)| cap.clone())
+                .ok_or_else(not_found)
+        }
+
+        fn query_ctrl(&self, _node: &str, cid: u32) -> io::Result<QueryCtrl> {
+            self.controls
+                .borrow()
+                .get(&cid)
+                .map(|&(q, _)| q)
+                .ok_or_else(not_found)
+        }
+
+        fn get_ctrl(&self, _node: &str, cid: u32) -> io::Result<i32> {
+            self.controls
+                .borrow()
+                .get(&cid)
+                .map(|&(_, value)| value)
+                .ok_or_else(not_found)
+        }
+
+        fn set_ctrl(&self, _node: &str, cid: u32, value: i32) -> io::Result<()> {
+            let mut controls = self.controls.borrow_mut();
+            let (q, current) = controls.get_mut(&cid).ok_or_else(not_found)?;
+            if value < q.minimum || value > q.maximum {
+                return Err(io::Error::from_raw_os_error(libc::ERANGE));
+            }
+            *current = value;
+            Ok(())
+        }
+    }
+
+    fn device() -> DeviceInfo {
+        DeviceInfo::named("HD Pro Webcam C920").with_path(NODE)
+    }
+
+    fn ids(name: &str) -> (PropertyType, i32) {
+        let p = registry::lookup(name).unwrap();
+        (p.property_type, p.id)
+    }
+
+    #[test]
+    fn enumerate_skips_nodes_that_cannot_capture() {
+        let backend = V4l2Backend::with_io(FakeIo::camera());
+        let devices = backend.enumerate().unwrap();
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].device_path.as_deref(), Some(NODE));
+        assert_eq!(devices[0].name.as_deref(), Some("HD Pro Webcam C920"));
+        assert_eq!(devices[0].location.as_deref(), Some("usb-0000:00:14.0-1.3"));
+    }
+
+    #[test]
+    fn every_mapping_names_a_registry_property() {
+        for mapping in CONTROL_MAPPINGS {
+            let p = registry::lookup(mapping.property).unwrap();
+            let found = find_mapping(p.property_type, p.id).unwrap();
+            assert_eq!(found.cid, mapping.cid, "{}", mapping.property);
+        }
+        assert_eq!(
+            find_mapping(PropertyType::VideoProcAmp, 0).unwrap().cid,
+            V4L2_CID_BRIGHTNESS
+        );
+        assert_eq!(
+            find_mapping(PropertyType::CameraControl, 4).unwrap().cid,
+            V4L2_CID_EXPOSURE_ABSOLUTE
+        );
+        // Roll has no V4L2 control
+        assert!(find_mapping(PropertyType::CameraControl, 2).is_err());
+    }
+
+    #[test]
+    fn unmapped_or_missing_controls_are_unsupported() {
+        let backend = V4l2Backend::with_io(FakeIo::camera());
+        let (t, id) = ids("Brightness");
+        assert!(backend.get_range(&device(), t, id).is_err());
+        let (t, id) = ids("Roll");
+        assert!(backend.get_range(&device(), t, id).is_err());
+    }
+
+    #[test]
+    fn identity_controls_pass_through() {
+        let io = FakeIo::camera().control(V4L2_CID_BRIGHTNESS, 0, 255, 128);
+        let backend = V4l2Backend::with_io(io);
+        let (t, id) = ids("Brightness");
+
+        let range = backend.get_range(&device(), t, id).unwrap();
+        assert_eq!(
+            range,
+            PropertyRange {
+                min: 0,
+                max: 255,
+                step: 1,
+                default: 128,
+                caps: FLAGS_MANUAL,
+            }
+        );
+        backend.set(&device(), t, id, 200, false).unwrap();
+        assert_eq!(backend.io.value(V4L2_CID_BRIGHTNESS), 200);
+        assert_eq!(backend.get(&device(), t, id).unwrap(), (200, FLAGS_MANUAL));
+    }
+
+    #[test]
+    fn exposure_converts_to_log2_seconds() {
+        // 100µs units: 3 is 0.3ms, 2047 is 204.7ms, 156 is 15.6ms (about 1/64s)
+        let io = FakeIo::camera()
+            .control(V4L2_CID_EXPOSURE_ABSOLUTE, 3, 2047, 156)
+            .control(
+                V4L2_CID_EXPOSURE_AUTO,
+                0,
+                3,
+                V4L2_EXPOSURE_APERTURE_PRIORITY,
+            );
+        let backend = V4l2Backend::with_io(io);
+        let (t, id) = ids("Exposure");
+
+        let range = backend.get_range(&device(), t, id).unwrap();
+        assert_eq!((range.min, range.max, range.default), (-12, -2, -6));
+        assert_eq!(range.step, 1);
+        assert_eq!(range.caps, FLAGS_MANUAL | FLAGS_AUTO);
+
+        backend.set(&device(), t, id, -6, false).unwrap();
+        // 2^-6 s = 15.625ms, rounded to 156
+        assert_eq!(backend.io.value(V4L2_CID_EXPOSURE_ABSOLUTE), 156);
+        assert_eq!(
+            backend.io.value(V4L2_CID_EXPOSURE_AUTO),
+            V4L2_EXPOSURE_MANUAL
+        );
+        assert_eq!(backend.get(&device(), t, id).unwrap(), (-6, FLAGS_MANUAL));
+
+        backend.set(&device(), t, id, -6, true).unwrap();
+        assert_eq!(
+            backend.io.value(V4L2_CID_EXPOSURE_AUTO),
+            V4L2_EXPOSURE_APERTURE_PRIORITY
+        );
+        assert_eq!(backend.get(&device(), t, id).unwrap(), (-6, FLAGS_AUTO));
+    }
+
+    #[test]
+    fn log2_exposure_rounding() {
+        let scale = Scale::Log2Exposure;
+        // Values below 1 are clamped rather than taking the log of zero
+        assert_eq!(scale.to_directshow(0), scale.to_directshow(1));
+        assert_eq!(scale.to_directshow(1), -13);
+        assert_eq!(scale.to_directshow(10000), 0);
+        assert_eq!(scale.to_v4l2(0), 10000);
+        assert_eq!(scale.to_v4l2(-13), 1);
+        for raw in -13..=3 {
+            assert_eq!(scale.to_directshow(scale.to_v4l2(raw)), raw);
+        }
+        assert_eq!(scale.step_to_directshow(50), 1);
+    }
+
+    #[test]
+    fn arc_seconds_convert_to_degrees() {
+        let scale = Scale::ArcSeconds;
+        assert_eq!(scale.to_directshow(36000), 10);
+        assert_eq!(scale.to_directshow(-36000), -10);
+        assert_eq!(scale.to_v4l2(-10), -36000);
+        assert_eq!(scale.step_to_directshow(3600), 1);
+        assert_eq!(scale.step_to_directshow(100), 1);
+    }
+
+    #[test]
+    fn focus_auto_switches_mode() {
+        let io = FakeIo::camera()
+            .control(V4L2_CID_FOCUS_ABSOLUTE, 0, 250, 0)
+            .control(V4L2_CID_FOCUS_AUTO, 0, 1, 1);
+        let backend = V4l2Backend::with_io(io);
+        let (t, id) = ids("Focus");
+
+        assert_eq!(
+            backend.get_range(&device(), t, id).unwrap().caps,
+            FLAGS_MANUAL | FLAGS_AUTO
+        );
+        assert_eq!(backend.get(&device(), t, id).unwrap(), (0, FLAGS_AUTO));
+
+        // A manual write turns the auto control off first
+        backend.set(&device(), t, id, 50, false).unwrap();
+        assert_eq!(backend.io.value(V4L2_CID_FOCUS_AUTO), 0);
+        assert_eq!(backend.io.value(V4L2_CID_FOCUS_ABSOLUTE), 50);
+        assert_eq!(backend.get(&device(), t, id).unwrap(), (50, FLAGS_MANUAL));
+
+        // Auto only touches the auto control
+        backend.set(&device(), t, id, 50, true).unwrap();
+        assert_eq!(backend.io.value(V4L2_CID_FOCUS_AUTO), 1);
+        assert_eq!(backend.io.value(V4L2_CID_FOCUS_ABSOLUTE), 50);
+    }
+
+    #[test]
+    fn white_balance_auto_switches_mode() {
+        let io = FakeIo::camera()
+            .control(V4L2_CID_WHITE_BALANCE_TEMPERATURE, 2000, 6500, 4000)
+            .control(V4L2_CID_AUTO_WHITE_BALANCE, 0, 1, 0);
+        let backend = V4l2Backend::with_io(io);
+        let (t, id) = ids("WhiteBalance");
+
+        assert_eq!(backend.get(&device(), t, id).unwrap(), (4000, FLAGS_MANUAL));
+        backend.set(&device(), t, id, 4000, true).unwrap();
+        assert_eq!(backend.io.value(V4L2_CID_AUTO_WHITE_BALANCE), 1);
+        assert_eq!(backend.get(&device(), t, id).unwrap(), (4000, FLAGS_AUTO));
+    }
+
+    #[test]
+    fn disabled_auto_control_is_ignored() {
+        let io = FakeIo::camera()
+            .control(V4L2_CID_WHITE_BALANCE_TEMPERATURE, 2000, 6500, 4000)
+            .control(V4L2_CID_AUTO_WHITE_BALANCE, 0, 1, 1);
+        io.controls
+            .borrow_mut()
+            .get_mut(&V4L2_CID_AUTO_WHITE_BALANCE)
+            .unwrap()
+            .0
+            .flags = V4L2_CTRL_FLAG_DISABLED;
+        let backend = V4l2Backend::with_io(io);
+        let (t, id) = ids("WhiteBalance");
+
+        assert_eq!(
+            backend.get_range(&device(), t, id).unwrap().caps,
+            FLAGS_MANUAL
+        );
+        assert_eq!(backend.get(&device(), t, id).unwrap(), (4000, FLAGS_MANUAL));
+    }
+
+    #[test]
+    fn auto_is_rejected_without_an_auto_control() {
+        let io = FakeIo::camera().control(V4L2_CID_CONTRAST, 0, 255, 128);
+        let backend = V4l2Backend::with_io(io);
+        let (t, id) = ids("Contrast");
+        let err = backend.set(&device(), t, id, 128, true).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Property Contrast does not support Auto mode"
+        );
+
+        // Focus has an auto control, but this camera doesn't
+        let io = FakeIo::camera().control(V4L2_CID_FOCUS_ABSOLUTE, 0, 250, 0);
+        let backend = V4l2Backend::with_io(io);
+        let (t, id) = ids("Focus");
+        let err = backend.set(&device(), t, id, 0, true).unwrap_err();
+        assert_eq!(err.to_string(), "Property Focus does not support Auto mode");
+    }
+
+    #[test]
+    fn iris_converts_to_tenth_f_stops() {
+        // f/1.4 to f/16 in hundredths, stepping by 0.1
+        let io = FakeIo::camera().control(V4L2_CID_IRIS_ABSOLUTE, 140, 1600, 280);
+        io.controls
+            .borrow_mut()
+            .get_mut(&V4L2_CID_IRIS_ABSOLUTE)
+            .unwrap()
+            .0
+            .step = 10;
+        let backend = V4l2Backend::with_io(io);
+        let (t, id) = ids("Iris");
+
+        let range = backend.get_range(&device(), t, id).unwrap();
+        assert_eq!(
+            (range.min, range.max, range.step, range.default),
+            (14, 160, 1, 28)
+        );
+        assert_eq!(backend.get(&device(), t, id).unwrap(), (28, FLAGS_MANUAL));
+        assert_eq!(
+            registry::lookup("Iris").unwrap().unit.format(28).as_deref(),
+            Some("28 (f/2.8)")
+        );
+
+        backend.set(&device(), t, id, 56, false).unwrap();
+        assert_eq!(backend.io.value(V4L2_CID_IRIS_ABSOLUTE), 560);
+        assert_eq!(backend.get(&device(), t, id).unwrap(), (56, FLAGS_MANUAL));
+    }
+
+    #[test]
+    fn powerline_frequency_menu_values_match_directshow() {
+        let io = FakeIo::camera().control(V4L2_CID_POWER_LINE_FREQUENCY, 0, 3, 2);
+        let backend = V4l2Backend::with_io(io);
+        let (t, id) = ids("PowerlineFrequency");
+        let labels = registry::lookup("PowerlineFrequency").unwrap();
+
+        let range = backend.get_range(&device(), t, id).unwrap();
+        assert_eq!((range.min, range.max, range.default), (0, 3, 2));
+        for (value, label) in [(0, "Disabled"), (1, "50Hz"), (2, "60Hz"), (3, "Auto")] {
+            backend.set(&device(), t, id, value, false).unwrap();
+            assert_eq!(backend.io.value(V4L2_CID_POWER_LINE_FREQUENCY), value);
+            let (read, flags) = backend.get(&device(), t, id).unwrap();
+            assert_eq!((read, flags), (value, FLAGS_MANUAL));
+            assert_eq!(labels.label(read), Some(label));
+        }
+    }
+}
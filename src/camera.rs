@@ -0,0 +1,590 @@
+/// Opened camera handle
+///
+/// A `Camera` pairs a snapshot of a device's `DeviceInfo` with the backend it came from.
+/// Reads refresh the snapshot from the backend, and writes are validated against the
+/// ranges the camera reported before they reach the backend.
+use crate::Error;
+use crate::backend::CameraBackend;
+use crate::profile::Mode;
+use crate::registry::{self, PropertyDef};
+use crate::webcam::{self, DeviceInfo, DeviceKind, PropertyInfo, PropertyValue};
+use std::time::Duration;
+use tracing::{debug, info, instrument, warn};
+
+/// How long `Camera::verify` waits by default before reading a written property back
+pub const DEFAULT_SETTLE: Duration = Duration::from_millis(50);
+
+/// How to handle a value that falls between a property's steps
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Snap {
+    /// Round to the closest step, rounding halfway values up
+    Nearest,
+    /// Round down to the step below
+    Down,
+    /// Round up to the step above
+    Up,
+    /// Reject the value
+    #[default]
+    Error,
+}
+
+/// A value resolved against a camera's property, ready to be written
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resolved {
+    /// The value as it will be reported once written
+    pub value: PropertyValue,
+    /// Raw value to write, or None to switch the property to Auto mode
+    pub raw: Option<i32>,
+    /// Raw value before it was snapped to the step grid, when snapping changed it
+    pub snapped_from: Option<i32>,
+}
+
+/// What a property reported when it was read back after a write
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReadBack {
+    /// Value the camera reports
+    pub value: Option<PropertyValue>,
+    /// Mode the camera reports, if the property supports Auto mode
+    pub mode: Option<Mode>,
+    /// Whether the value and mode match what was written
+    pub matches: bool,
+}
+
+/// Earlier state of the properties changed by a batch of writes, so the batch can be undone
+/// with `Camera::roll_back`
+#[derive(Debug, Clone, Default)]
+pub struct Undo {
+    saved: Vec<PropertyInfo>,
+}
+
+impl Undo {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether no write has been saved yet
+    pub fn is_empty(&self) -> bool {
+        self.saved.is_empty()
+    }
+}
+
+/// A single video capture device, opened through a backend
+pub struct Camera<'a> {
+    backend: &'a dyn CameraBackend,
+    index: usize,
+    info: DeviceInfo,
+}
+
+impl<'a> Camera<'a> {
+    /// Open every camera the backend can see, in enumeration order
+    #[instrument(skip(backend))]
+    pub fn enumerate(backend: &'a dyn CameraBackend) -> Result<Vec<Camera<'a>>, Error> {
+        let devices = webcam::enumerate_devices(backend)?;
+        debug!(device_count = devices.len(), "Cameras enumerated");
+        Ok(devices
+            .into_iter()
+            .enumerate()
+            .map(|(index, info)| Camera {
+                backend,
+                index,
+                info,
+            })
+            .collect())
+    }
+
+    /// Open the camera at `index` in enumeration order
+    pub fn open(backend: &'a dyn CameraBackend, index: usize) -> Result<Camera<'a>, Error> {
+        let mut cameras = Self::enumerate(backend)?;
+        let count = cameras.len();
+        if index >= count {
+            return Err(Error::CameraNotFound { index, count });
+        }
+        Ok(cameras.swap_remove(index))
+    }
+
+    /// Position of the camera in enumeration order
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Friendly name of the camera, or "Unknown" when the device has none
+    pub fn name(&self) -> &str {
+        self.info.name.as_deref().unwrap_or("Unknown")
+    }
+
+    /// Device path identifying the camera to the backend
+    pub fn device_path(&self) -> Option<&str> {
+        self.info.device_path.as_deref()
+    }
+
+    /// Whether the camera is real hardware or a software camera
+    pub fn kind(&self) -> DeviceKind {
+        self.info.kind()
+    }
+
+    /// Device information as of the last enumeration or read
+    pub fn info(&self) -> &DeviceInfo {
+        &self.info
+    }
+
+    /// All properties the camera supports, VideoProcAmp first
+    pub fn properties(&self) -> impl Iterator<Item = &PropertyInfo> {
+        self.info
+            .video_proc_amp_properties
+            .iter()
+            .chain(&self.info.camera_control_properties)
+    }
+
+    /// Look up a supported property by name or alias (case-insensitive)
+    pub fn property(&self, property: &str) -> Result<&PropertyInfo, Error> {
+        validate_property_name(property)?;
+        let name = registry::lookup(property).map_or(property, |p| p.name);
+        self.properties()
+            .find(|p| p.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| Error::PropertyNotFound {
+                property: property.to_string(),
+                camera: self.name().to_string(),
+            })
+    }
+
+    // Mutable lookup used to keep the snapshot in step with writes
+    fn property_mut(&mut self, property: &str) -> Option<&mut PropertyInfo> {
+        self.info
+            .video_proc_amp_properties
+            .iter_mut()
+            .chain(&mut self.info.camera_control_properties)
+            .find(|p| p.name.eq_ignore_ascii_case(property))
+    }
+
+    /// Read the current value and flags of a property from the camera
+    pub fn get(&mut self, property: &str) -> Result<&PropertyInfo, Error> {
+        let (name, def) = {
+            let p = self.property(property)?;
+            (p.name.clone(), definition(p)?)
+        };
+        let (value, flags) = self.backend.get(&self.info, def.property_type, def.id)?;
+
+        let p = self.property_mut(&name).expect("property was found above");
+        p.current = Some(value);
+        p.current_flags = Some(flags);
+        Ok(p)
+    }
+
+    /// Write a property, rejecting values that fall between the camera's steps.
+    /// `Manual`, `Label`, `Percent` and relative values are resolved and checked against the
+    /// camera's range and written in Manual mode; `Auto` switches the property to Auto mode.
+    /// Returns the value written, with labels in their canonical spelling and computed
+    /// values resolved to the raw value.
+    pub fn set(&mut self, property: &str, value: &PropertyValue) -> Result<PropertyValue, Error> {
+        self.set_snapped(property, value, Snap::Error)
+    }
+
+    /// Write a property, handling values between the camera's steps according to `snap`
+    pub fn set_snapped(
+        &mut self,
+        property: &str,
+        value: &PropertyValue,
+        snap: Snap,
+    ) -> Result<PropertyValue, Error> {
+        let resolved = self.resolve(property, value, snap)?;
+        self.apply(property, &resolved)
+    }
+
+    /// Work out what writing `value` would do without touching the camera: the raw value
+    /// after range and step checks, and whether snapping moved it
+    pub fn resolve(
+        &self,
+        property: &str,
+        value: &PropertyValue,
+        snap: Snap,
+    ) -> Result<Resolved, Error> {
+        let p = self.property(property)?;
+        let raw = value.raw(p).map_err(|e| Error::InvalidValue {
+            property: p.name.clone(),
+            value: value.to_string(),
+            reason: format!("{:#}", e),
+        })?;
+
+        let Some(raw) = raw else {
+            return Ok(Resolved {
+                value: PropertyValue::Auto,
+                raw: None,
+                snapped_from: None,
+            });
+        };
+
+        if let (Some(min), Some(max)) = (p.min, p.max)
+            && (raw < min || raw > max)
+        {
+            return Err(Error::OutOfRange {
+                property: p.name.clone(),
+                value: raw,
+                min,
+                max,
+            });
+        }
+
+        let snapped = snap_to_step(p, raw, snap)?;
+        let written = match value {
+            PropertyValue::Manual(_) if snapped == raw => value.clone(),
+            _ => PropertyValue::from_raw(&p.name, snapped),
+        };
+        Ok(Resolved {
+            value: written,
+            raw: Some(snapped),
+            snapped_from: (snapped != raw).then_some(raw),
+        })
+    }
+
+    /// Write a value previously worked out by `resolve`. Returns the value written.
+    pub fn apply(&mut self, property: &str, resolved: &Resolved) -> Result<PropertyValue, Error> {
+        match resolved.raw {
+            Some(raw) => self.write(property, raw, false)?,
+            None => {
+                // Drivers ignore the value in Auto mode, but some still range-check it,
+                // so pass the current value through.
+                let p = self.property(property)?;
+                let current = p.current.or(p.default).unwrap_or(0);
+                self.write(property, current, true)?;
+            }
+        }
+        Ok(resolved.value.clone())
+    }
+
+    /// Read a property back after `resolved` was written to it, to catch drivers that accept
+    /// a write and then clamp or ignore the value. Waits `settle` first, or `DEFAULT_SETTLE`
+    /// when it is None and the backend doesn't apply writes immediately.
+    pub fn verify(
+        &mut self,
+        property: &str,
+        resolved: &Resolved,
+        settle: Option<Duration>,
+    ) -> Result<ReadBack, Error> {
+        let settle = settle.unwrap_or(if self.backend.applies_writes_immediately() {
+            Duration::ZERO
+        } else {
+            DEFAULT_SETTLE
+        });
+        if !settle.is_zero() {
+            std::thread::sleep(settle);
+        }
+
+        let p = self.get(property)?;
+        let auto = p
+            .current_flags
+            .is_some_and(|flags| flags & webcam::FLAGS_AUTO != 0);
+        let matches = match resolved.raw {
+            Some(raw) => p.current == Some(raw) && !auto,
+            None => auto,
+        };
+        Ok(ReadBack {
+            value: p.current.map(|v| PropertyValue::from_raw(&p.name, v)),
+            mode: Mode::of(p),
+            matches,
+        })
+    }
+
+    /// Like `apply`, but saves the property's state from before the write to `undo` once the
+    /// camera accepts it
+    pub fn apply_undoable(
+        &mut self,
+        property: &str,
+        resolved: &Resolved,
+        undo: &mut Undo,
+    ) -> Result<PropertyValue, Error> {
+        let before = self.property(property)?.clone();
+        let written = self.apply(property, resolved)?;
+        undo.saved.push(before);
+        Ok(written)
+    }
+
+    /// Put back every property saved in `undo`, newest first, so the camera ends up as it was
+    /// before the batch. Returns each property's name with the result of restoring it, in the
+    /// order they were restored; a failure doesn't stop the others from being restored.
+    pub fn roll_back(&mut self, undo: Undo) -> Vec<(String, Result<PropertyValue, Error>)> {
+        undo.saved
+            .into_iter()
+            .rev()
+            .map(|before| {
+                let result = self.restore(&before);
+                match &result {
+                    Ok(_) => info!(device_index = self.index, property = %before.name, "Write rolled back"),
+                    Err(e) => warn!(device_index = self.index, property = %before.name, error = %e, "Failed to roll back write"),
+                }
+                (before.name, result)
+            })
+            .collect()
+    }
+
+    /// Work out what `reset` would write without touching the camera
+    pub fn resolve_default(&self, property: &str) -> Result<Resolved, Error> {
+        let p = self.property(property)?;
+        let default = p.default.ok_or_else(|| Error::NoDefault {
+            property: p.name.clone(),
+        })?;
+        // The default is the camera's own value, so it is written without step checks
+        Ok(Resolved {
+            value: PropertyValue::from_raw(&p.name, default),
+            raw: Some(default),
+            snapped_from: None,
+        })
+    }
+
+    /// Restore a property to the camera's default value in Manual mode.
+    /// Returns the value written.
+    pub fn reset(&mut self, property: &str) -> Result<PropertyValue, Error> {
+        let resolved = self.resolve_default(property)?;
+        self.apply(property, &resolved)
+    }
+
+    /// Put a property back into the state saved in `saved`, a copy of the `PropertyInfo`
+    /// taken before it was changed: the saved value in Manual mode, or Auto mode if that
+    /// was the saved mode. Returns the value written.
+    pub fn restore(&mut self, saved: &PropertyInfo) -> Result<PropertyValue, Error> {
+        let auto = saved
+            .current_flags
+            .is_some_and(|flags| flags & webcam::FLAGS_AUTO != 0);
+        let raw = match (saved.current, auto) {
+            (Some(raw), _) => raw,
+            // Drivers ignore the value in Auto mode, but some still range-check it
+            (None, true) => saved.default.unwrap_or(0),
+            (None, false) => {
+                return Err(Error::NothingToRestore {
+                    property: saved.name.clone(),
+                });
+            }
+        };
+        self.write(&saved.name, raw, auto)?;
+        Ok(if auto {
+            PropertyValue::Auto
+        } else {
+            PropertyValue::from_raw(&saved.name, raw)
+        })
+    }
+
+    // Dispatch a validated write to the backend and update the snapshot on success
+    fn write(&mut self, property: &str, value: i32, auto: bool) -> Result<(), Error> {
+        let (name, def) = {
+            let p = self.property(property)?;
+            (p.name.clone(), definition(p)?)
+        };
+
+        webcam::set_property(self.backend, &self.info, def, value, auto)?;
+        info!(device_index = self.index, device_name = self.name(), property = %name, value, auto, "Property set successfully");
+
+        let p = self.property_mut(&name).expect("property was found above");
+        if !auto {
+            p.current = Some(value);
+        }
+        p.current_flags = Some(if auto {
+            webcam::FLAGS_AUTO
+        } else {
+            webcam::FLAGS_MANUAL
+        });
+        Ok(())
+    }
+}
+
+// Align a raw value with the property's step grid, which starts at its minimum. Snapped
+// values that would pass the maximum fall back to the step below.
+fn snap_to_step(property: &PropertyInfo, raw: i32, snap: Snap) -> Result<i32, Error> {
+    let step = i64::from(property.step.unwrap_or(1));
+    if step <= 1 {
+        return Ok(raw);
+    }
+    let base = i64::from(property.min.unwrap_or(0));
+    let value = i64::from(raw);
+    let below = value - (value - base).rem_euclid(step);
+    if below == value {
+        return Ok(raw);
+    }
+    let above = below + step;
+
+    let snapped = match snap {
+        Snap::Nearest if (value - below) * 2 >= step => above,
+        Snap::Nearest | Snap::Down => below,
+        Snap::Up => above,
+        Snap::Error => {
+            return Err(Error::OffStep {
+                property: property.name.clone(),
+                value: raw,
+                step: step as i32,
+                below: below as i32,
+                above: above as i32,
+            });
+        }
+    };
+    let max = property.max.map_or(i64::from(i32::MAX), i64::from);
+    Ok(if snapped > max { below } else { snapped } as i32)
+}
+
+// Sanitize property names before they are used for lookups
+fn validate_property_name(property: &str) -> Result<(), Error> {
+    // Only allow alphanumeric characters
+    if !property.chars().all(|c| c.is_alphanumeric()) {
+        return Err(Error::InvalidPropertyName(
+            "contains non-alphanumeric characters".to_string(),
+        ));
+    }
+
+    // Limit property name length to prevent potential issues
+    if property.len() > 64 {
+        return Err(Error::InvalidPropertyName(
+            "exceeds maximum length".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+// Registry entry for a property reported by the backend
+fn definition(property: &PropertyInfo) -> Result<&'static PropertyDef, Error> {
+    property.definition().ok_or_else(|| {
+        Error::Backend(anyhow::anyhow!(
+            "Unknown {} property: {}",
+            property.property_type,
+            property.name
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::sample_backend;
+
+    fn focus(min: i32, max: i32, step: i32) -> PropertyInfo {
+        PropertyInfo::ranged("Focus", min, max, step)
+    }
+
+    #[test]
+    fn values_on_the_grid_are_kept() {
+        let p = focus(0, 250, 5);
+        for snap in [Snap::Nearest, Snap::Down, Snap::Up, Snap::Error] {
+            assert_eq!(snap_to_step(&p, 0, snap).unwrap(), 0);
+            assert_eq!(snap_to_step(&p, 85, snap).unwrap(), 85);
+            assert_eq!(snap_to_step(&p, 250, snap).unwrap(), 250);
+        }
+        // A step of 1 (or none) accepts everything
+        assert_eq!(snap_to_step(&focus(0, 255, 1), 7, Snap::Error).unwrap(), 7);
+    }
+
+    #[test]
+    fn off_grid_values_snap() {
+        let p = focus(0, 250, 5);
+        assert_eq!(snap_to_step(&p, 87, Snap::Nearest).unwrap(), 85);
+        assert_eq!(snap_to_step(&p, 82, Snap::Nearest).unwrap(), 80);
+        // Halfway rounds up
+        assert_eq!(
+            snap_to_step(&focus(0, 250, 4), 6, Snap::Nearest).unwrap(),
+            8
+        );
+        assert_eq!(snap_to_step(&p, 84, Snap::Down).unwrap(), 80);
+        assert_eq!(snap_to_step(&p, 81, Snap::Up).unwrap(), 85);
+    }
+
+    #[test]
+    fn grid_starts_at_the_minimum() {
+        let p = focus(-11, 9, 5);
+        assert_eq!(snap_to_step(&p, -6, Snap::Error).unwrap(), -6);
+        assert_eq!(snap_to_step(&p, -5, Snap::Down).unwrap(), -6);
+        assert_eq!(snap_to_step(&p, -10, Snap::Up).unwrap(), -6);
+    }
+
+    #[test]
+    fn snapping_never_passes_the_maximum() {
+        let p = focus(0, 254, 5);
+        assert_eq!(snap_to_step(&p, 253, Snap::Up).unwrap(), 250);
+        assert_eq!(snap_to_step(&p, 254, Snap::Nearest).unwrap(), 250);
+    }
+
+    #[test]
+    fn off_grid_values_are_rejected_by_default() {
+        let err = snap_to_step(&focus(0, 250, 5), 87, Snap::default()).unwrap_err();
+        match err {
+            Error::OffStep {
+                value,
+                step,
+                below,
+                above,
+                ..
+            } => assert_eq!((value, step, below, above), (87, 5, 85, 90)),
+            other => panic!("unexpected error: {}", other),
+        }
+    }
+
+    #[test]
+    fn roll_back_restores_newest_first() {
+        let backend = sample_backend();
+        let mut camera = Camera::open(&backend, 0).unwrap();
+        let mut undo = Undo::new();
+
+        for (property, value) in [("Brightness", 200), ("Brightness", 50), ("Contrast", 10)] {
+            let resolved = camera
+                .resolve(property, &PropertyValue::Manual(value), Snap::Error)
+                .unwrap();
+            camera
+                .apply_undoable(property, &resolved, &mut undo)
+                .unwrap();
+        }
+        // A rejected write isn't saved
+        let resolved = Resolved {
+            value: PropertyValue::Auto,
+            raw: None,
+            snapped_from: None,
+        };
+        assert!(
+            camera
+                .apply_undoable("Contrast", &resolved, &mut undo)
+                .is_err()
+        );
+
+        let restored: Vec<_> = camera
+            .roll_back(undo)
+            .into_iter()
+            .map(|(name, result)| (name, result.unwrap()))
+            .collect();
+        assert_eq!(
+            restored,
+            [
+                ("Contrast".to_string(), PropertyValue::Manual(128)),
+                ("Brightness".to_string(), PropertyValue::Manual(200)),
+                ("Brightness".to_string(), PropertyValue::Manual(128)),
+            ]
+        );
+        assert_eq!(camera.get("Brightness").unwrap().current, Some(128));
+        assert_eq!(camera.get("Contrast").unwrap().current, Some(128));
+    }
+
+    #[test]
+    fn verify_compares_the_value_read_back() {
+        let backend = sample_backend();
+        let mut camera = Camera::open(&backend, 0).unwrap();
+
+        let resolved = camera
+            .resolve("Brightness", &PropertyValue::Manual(200), Snap::Error)
+            .unwrap();
+        camera.apply("Brightness", &resolved).unwrap();
+        let read_back = camera.verify("Brightness", &resolved, None).unwrap();
+        assert_eq!(
+            read_back,
+            ReadBack {
+                value: Some(PropertyValue::Manual(200)),
+                mode: None,
+                matches: true,
+            }
+        );
+
+        // As if the driver had clamped the write
+        let expected = Resolved {
+            value: PropertyValue::Manual(210),
+            raw: Some(210),
+            snapped_from: None,
+        };
+        let read_back = camera
+            .verify("Brightness", &expected, Some(Duration::ZERO))
+            .unwrap();
+        assert!(!read_back.matches);
+        assert_eq!(read_back.value, Some(PropertyValue::Manual(200)));
+    }
+}
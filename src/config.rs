@@ -0,0 +1,203 @@
+/// Configuration files
+///
+/// Settings are read from a per-machine file shared by every user and a per-user file,
+/// whose entries take precedence:
+///
+/// - Windows: `%ProgramData%\wincamcfg\config.json` and `%APPDATA%\wincamcfg\config.json`
+/// - Linux: `/etc/wincamcfg/config.json` and `$XDG_CONFIG_HOME/wincamcfg/config.json`
+///   (`~/.config/wincamcfg/config.json` when `XDG_CONFIG_HOME` is unset)
+///
+/// A missing file is the same as an empty one.
+use crate::alias::CameraAlias;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use tracing::{debug, instrument};
+
+/// Config file format version written by this build
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Which config file a setting lives in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    /// Shared by every user of the machine; writing it usually needs admin rights
+    Machine,
+    /// The current user's own settings
+    User,
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Machine => "machine",
+            Self::User => "user",
+        })
+    }
+}
+
+/// Contents of one config file
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Config {
+    pub version: u32,
+    /// Camera aliases by name
+    #[serde(default)]
+    pub aliases: BTreeMap<String, CameraAlias>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            aliases: BTreeMap::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Location of the config file for a scope, or None if the environment doesn't say
+    /// where it should be
+    pub fn path(scope: Scope) -> Option<PathBuf> {
+        dir(scope).map(|dir| dir.join("config.json"))
+    }
+
+    /// Load the config file for a scope, or an empty config if it doesn't exist
+    #[instrument]
+    pub fn load(scope: Scope) -> Result<Self> {
+        let Some(path) = Self::path(scope) else {
+            debug!("No config location for this scope");
+            return Ok(Self::default());
+        };
+        Self::load_file(&path)
+    }
+
+    /// Load a config file, or an empty config if it doesn't exist
+    pub fn load_file(path: &Path) -> Result<Self> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("Failed to read config file '{}'", path.display()));
+            }
+        };
+        let config: Self = serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse config file '{}'", path.display()))?;
+        if config.version > CONFIG_VERSION {
+            anyhow::bail!(
+                "Config file '{}' is version {}, but this build only understands version {}",
+                path.display(),
+                config.version,
+                CONFIG_VERSION
+            );
+        }
+        debug!(path = %path.display(), alias_count = config.aliases.len(), "Config loaded");
+        Ok(config)
+    }
+
+    /// Write the config file for a scope, creating its directory if needed
+    #[instrument(skip(self))]
+    pub fn save(&self, scope: Scope) -> Result<()> {
+        let path = Self::path(scope)
+            .with_context(|| format!("Cannot locate the {} config directory", scope))?;
+        self.save_file(&path)
+    }
+
+    /// Write a config file, creating its directory if needed
+    pub fn save_file(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).with_context(|| {
+                format!("Failed to create config directory '{}'", dir.display())
+            })?;
+        }
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize config")?;
+        std::fs::write(path, json)
+            .with_context(|| format!("Failed to write config file '{}'", path.display()))
+    }
+}
+
+/// Directory holding the config file of a scope, and any state the tool keeps alongside it
+pub fn dir(scope: Scope) -> Option<PathBuf> {
+    config_dir(scope).map(|dir| dir.join("wincamcfg"))
+}
+
+// Base directory for config files of a scope
+#[cfg(windows)]
+fn config_dir(scope: Scope) -> Option<PathBuf> {
+    let var = match scope {
+        Scope::Machine => "ProgramData",
+        Scope::User => "APPDATA",
+    };
+    std::env::var_os(var).map(PathBuf::from)
+}
+
+// Base directory for config files of a scope
+#[cfg(not(windows))]
+fn config_dir(scope: Scope) -> Option<PathBuf> {
+    match scope {
+        Scope::Machine => Some(PathBuf::from("/etc")),
+        Scope::User => std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Fresh directory under the system temp dir, unique to the test
+    fn temp_dir(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("wincamcfg-{}-{}", test, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn missing_file_is_an_empty_config() {
+        let dir = temp_dir("missing");
+        let config = Config::load_file(&dir.join("config.json")).unwrap();
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert!(config.aliases.is_empty());
+    }
+
+    #[test]
+    fn saved_config_loads_back() {
+        let dir = temp_dir("round-trip");
+        let path = dir.join("wincamcfg").join("config.json");
+        let mut config = Config::default();
+        config.aliases.insert(
+            "podium".to_string(),
+            CameraAlias {
+                camera_name: Some("Razer Kiyo Pro".to_string()),
+                device_path: Some(r"\\?\usb#vid_1532&pid_0e05#A1B2C3D4#{x}".to_string()),
+                vendor_id: Some(0x1532),
+                product_id: Some(0x0e05),
+                serial: Some("A1B2C3D4".to_string()),
+            },
+        );
+        // The directory is created on save
+        config.save_file(&path).unwrap();
+        let loaded = Config::load_file(&path).unwrap();
+        assert_eq!(loaded.aliases, config.aliases);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn newer_versions_are_rejected() {
+        let dir = temp_dir("version");
+        let path = dir.join("config.json");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(&path, r#"{"version": 99, "aliases": {}}"#).unwrap();
+        let err = Config::load_file(&path).unwrap_err();
+        assert!(err.to_string().contains("version 99"), "{}", err);
+
+        std::fs::write(&path, "{").unwrap();
+        let err = Config::load_file(&path).unwrap_err();
+        assert!(err.to_string().contains("Failed to parse"), "{}", err);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
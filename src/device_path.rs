@@ -0,0 +1,306 @@
+/// Device path parsing
+///
+/// Windows identifies capture devices by a device interface path such as
+/// `\\?\usb#vid_046d&pid_082d&mi_00#7&2a8e9e3f&0&0000#{65e8773d-...}\global`: the bus, the
+/// hardware ID (vendor, product and interface), the instance ID (a serial number when the
+/// device has one), and the interface class GUID. This module splits that string into its
+/// fields, so cameras can be matched by model or by unit without string hacks.
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+/// Bus a device is attached to, from the first segment of its path
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Bus {
+    Usb,
+    Pci,
+    /// Software device, e.g. a Windows 11 virtual camera
+    Swd,
+    /// Root-enumerated device created by a driver rather than by hardware
+    Virtual,
+    #[serde(untagged)]
+    Other(String),
+}
+
+impl fmt::Display for Bus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Usb => "usb",
+            Self::Pci => "pci",
+            Self::Swd => "swd",
+            Self::Virtual => "virtual",
+            Self::Other(bus) => bus,
+        })
+    }
+}
+
+/// Fields parsed from a device path. Anything the path does not carry is None.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DevicePath {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bus: Option<Bus>,
+    /// USB vendor ID (or PCI vendor), serialized as 4 hex digits
+    #[serde(
+        serialize_with = "serialize_hex_id",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub vendor_id: Option<u16>,
+    /// USB product ID (or PCI device), serialized as 4 hex digits
+    #[serde(
+        serialize_with = "serialize_hex_id",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub product_id: Option<u16>,
+    /// Interface number of a composite USB device (`mi_00`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interface: Option<u8>,
+    /// Instance ID assigned by Windows
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    /// Serial number reported by the device, when the instance ID is one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub serial: Option<String>,
+    /// Device interface class GUID, including braces
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub class_guid: Option<String>,
+}
+
+impl DevicePath {
+    /// Parse a device path. Never fails; paths that are not Windows device interface paths
+    /// (e.g. `/dev/video0`) give an empty `DevicePath`.
+    pub fn parse(path: &str) -> Self {
+        let mut parsed = Self::default();
+
+        // \\?\usb#vid_046d&pid_082d&mi_00#INSTANCE#{guid}\global
+        let Some(body) = path.strip_prefix(r"\\?\") else {
+            return parsed;
+        };
+        let mut segments = body.split('#');
+
+        parsed.bus = segments.next().filter(|bus| !bus.is_empty()).map(|bus| {
+            match bus.to_ascii_lowercase().as_str() {
+                "usb" => Bus::Usb,
+                "pci" => Bus::Pci,
+                "swd" => Bus::Swd,
+                "root" => Bus::Virtual,
+                other => Bus::Other(other.to_string()),
+            }
+        });
+
+        if let Some(hardware_id) = segments.next() {
+            for part in hardware_id.split('&') {
+                let Some((key, value)) = part.split_once('_') else {
+                    continue;
+                };
+                match key.to_ascii_lowercase().as_str() {
+                    "vid" | "ven" => parsed.vendor_id = u16::from_str_radix(value, 16).ok(),
+                    "pid" | "dev" => parsed.product_id = u16::from_str_radix(value, 16).ok(),
+                    "mi" => parsed.interface = u8::from_str_radix(value, 16).ok(),
+                    _ => {}
+                }
+            }
+        }
+
+        if let Some(instance) = segments.next().filter(|i| !i.is_empty()) {
+            // Windows generates instance IDs containing '&' for USB devices without a serial
+            // number (and for each interface of a composite device). Other buses number
+            // their instances, so only USB instance IDs can be serial numbers.
+            if parsed.bus == Some(Bus::Usb) && !instance.contains('&') {
+                parsed.serial = Some(instance.to_string());
+            }
+            parsed.instance = Some(instance.to_string());
+        }
+
+        // The reference string after the GUID (e.g. "\global") is not part of it
+        parsed.class_guid = segments
+            .next()
+            .map(|rest| rest.split('\\').next().unwrap_or(rest))
+            .filter(|guid| guid.starts_with('{') && guid.ends_with('}'))
+            .map(str::to_string);
+
+        parsed
+    }
+
+    /// Short label telling this device apart from others of the same model: the serial
+    /// number, or else the part of the instance ID Windows derives from the USB port
+    pub fn discriminator(&self) -> Option<String> {
+        if let Some(ref serial) = self.serial {
+            return Some(format!("serial {}", serial));
+        }
+        // Instance IDs look like 7&2a8e9e3f&0&0000; the hash identifies the parent hub port
+        let instance = self.instance.as_deref()?;
+        let port = instance.split('&').nth(1).unwrap_or(instance);
+        Some(format!("instance {}", port))
+    }
+
+    /// Whether nothing could be parsed from the path
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl fmt::Display for DevicePath {
+    /// Short summary such as `usb 046d:082d, interface 0` or `usb 1532:0e05, serial A1B2`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        let ids = self
+            .vendor_id
+            .zip(self.product_id)
+            .map(|(vid, pid)| format!("{:04x}:{:04x}", vid, pid));
+        match (&self.bus, ids) {
+            (Some(bus), Some(ids)) => parts.push(format!("{} {}", bus, ids)),
+            (Some(bus), None) => parts.push(bus.to_string()),
+            (None, Some(ids)) => parts.push(ids),
+            (None, None) => {}
+        }
+        if let Some(interface) = self.interface {
+            parts.push(format!("interface {}", interface));
+        }
+        if let Some(ref serial) = self.serial {
+            parts.push(format!("serial {}", serial));
+        }
+        f.write_str(&parts.join(", "))
+    }
+}
+
+// Serialize a USB/PCI ID as 4 lowercase hex digits, the way it appears in device paths
+pub(crate) fn serialize_hex_id<S: Serializer>(
+    id: &Option<u16>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match id {
+        Some(id) => serializer.collect_str(&format_args!("{:04x}", id)),
+        None => serializer.serialize_none(),
+    }
+}
+
+// Deserialize a USB/PCI ID written by `serialize_hex_id`
+pub(crate) fn deserialize_hex_id<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<u16>, D::Error> {
+    Option::<String>::deserialize(deserializer)?
+        .map(|id| u16::from_str_radix(&id, 16).map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GUID: &str = "{65e8773d-8f56-11d0-a3b9-00a0c9223196}";
+
+    #[test]
+    fn usb_composite_interface() {
+        let parsed = DevicePath::parse(&format!(
+            r"\\?\usb#vid_046d&pid_082d&mi_00#7&2a8e9e3f&0&0000#{}\global",
+            GUID
+        ));
+        assert_eq!(parsed.bus, Some(Bus::Usb));
+        assert_eq!(parsed.vendor_id, Some(0x046d));
+        assert_eq!(parsed.product_id, Some(0x082d));
+        assert_eq!(parsed.interface, Some(0));
+        assert_eq!(parsed.instance.as_deref(), Some("7&2a8e9e3f&0&0000"));
+        assert_eq!(parsed.serial, None);
+        assert_eq!(parsed.class_guid.as_deref(), Some(GUID));
+        assert_eq!(parsed.to_string(), "usb 046d:082d, interface 0");
+    }
+
+    #[test]
+    fn usb_with_serial_number() {
+        let parsed = DevicePath::parse(&format!(r"\\?\USB#VID_1532&PID_0E05#A1B2C3D4#{}", GUID));
+        assert_eq!(parsed.bus, Some(Bus::Usb));
+        assert_eq!(parsed.vendor_id, Some(0x1532));
+        assert_eq!(parsed.product_id, Some(0x0e05));
+        assert_eq!(parsed.interface, None);
+        assert_eq!(parsed.instance.as_deref(), Some("A1B2C3D4"));
+        assert_eq!(parsed.serial.as_deref(), Some("A1B2C3D4"));
+        assert_eq!(parsed.class_guid.as_deref(), Some(GUID));
+        assert_eq!(parsed.to_string(), "usb 1532:0e05, serial A1B2C3D4");
+    }
+
+    #[test]
+    fn usb_interface_with_hex_number() {
+        let parsed = DevicePath::parse(r"\\?\usb#vid_046d&pid_085e&mi_0a#8&1&0&000a#{x");
+        assert_eq!(parsed.interface, Some(10));
+        // Unbalanced braces are not a GUID
+        assert_eq!(parsed.class_guid, None);
+    }
+
+    #[test]
+    fn pci_vendor_and_device() {
+        let parsed = DevicePath::parse(&format!(
+            r"\\?\pci#ven_8086&dev_9a19&subsys_0a5e1028&rev_01#3&11583659&0&A0#{}",
+            GUID
+        ));
+        assert_eq!(parsed.bus, Some(Bus::Pci));
+        assert_eq!(parsed.vendor_id, Some(0x8086));
+        assert_eq!(parsed.product_id, Some(0x9a19));
+        assert_eq!(parsed.serial, None);
+        assert_eq!(parsed.to_string(), "pci 8086:9a19");
+    }
+
+    #[test]
+    fn software_and_root_devices() {
+        let parsed = DevicePath::parse(&format!(
+            r"\\?\swd#vcamdevapi#{{0f8a2e4c-5a70-4d52-b8e1-3a5fbb9b9bb4}}#{}",
+            GUID
+        ));
+        assert_eq!(parsed.bus, Some(Bus::Swd));
+        assert_eq!(parsed.vendor_id, None);
+        assert_eq!(parsed.serial, None);
+
+        let parsed = DevicePath::parse(&format!(r"\\?\root#image#0000#{}\global", GUID));
+        assert_eq!(parsed.bus, Some(Bus::Virtual));
+        assert_eq!(parsed.instance.as_deref(), Some("0000"));
+        // Root devices are numbered, not serial numbers
+        assert_eq!(parsed.serial, None);
+        assert_eq!(parsed.class_guid.as_deref(), Some(GUID));
+        assert_eq!(parsed.to_string(), "virtual");
+    }
+
+    #[test]
+    fn unknown_bus_is_kept() {
+        let parsed = DevicePath::parse(r"\\?\display#int3470#4&1&0&uid13424#{x}");
+        assert_eq!(parsed.bus, Some(Bus::Other("display".to_string())));
+    }
+
+    #[test]
+    fn non_windows_paths_are_empty() {
+        for path in ["/dev/video0", "simulated#1", "", r"\\?\"] {
+            assert!(DevicePath::parse(path).is_empty(), "{}", path);
+        }
+    }
+
+    #[test]
+    fn discriminator_prefers_serial_then_port() {
+        let serial = DevicePath::parse(r"\\?\usb#vid_1532&pid_0e05#A1B2C3D4#{x}");
+        assert_eq!(serial.discriminator().as_deref(), Some("serial A1B2C3D4"));
+
+        let composite = DevicePath::parse(r"\\?\usb#vid_046d&pid_085e&mi_00#7&2a8e9e3f&0&0000#{x}");
+        assert_eq!(
+            composite.discriminator().as_deref(),
+            Some("instance 2a8e9e3f")
+        );
+
+        assert_eq!(DevicePath::parse("/dev/video0").discriminator(), None);
+    }
+
+    #[test]
+    fn hex_ids_round_trip_through_json() {
+        #[derive(Serialize, Deserialize)]
+        struct Ids {
+            #[serde(
+                serialize_with = "serialize_hex_id",
+                deserialize_with = "deserialize_hex_id"
+            )]
+            vendor_id: Option<u16>,
+        }
+        let json = serde_json::to_string(&Ids {
+            vendor_id: Some(0x046d),
+        })
+        .unwrap();
+        assert_eq!(json, r#"{"vendor_id":"046d"}"#);
+        let ids: Ids = serde_json::from_str(&json).unwrap();
+        assert_eq!(ids.vendor_id, Some(0x046d));
+    }
+}
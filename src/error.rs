@@ -0,0 +1,98 @@
+/// Errors returned by the `Camera` API
+use std::fmt;
+
+/// Structured error for camera operations
+///
+/// Each variant carries enough context for a caller to decide what to do without parsing
+/// the message. Failures inside the backend (COM/ioctl errors) are kept as `Backend`.
+#[derive(Debug)]
+pub enum Error {
+    /// No camera exists at the requested index
+    CameraNotFound { index: usize, count: usize },
+    /// The property name is malformed
+    InvalidPropertyName(String),
+    /// The camera does not expose the property
+    PropertyNotFound { property: String, camera: String },
+    /// The value string could not be parsed for the property
+    InvalidValue {
+        property: String,
+        value: String,
+        reason: String,
+    },
+    /// The value is outside the range the camera reports for the property
+    OutOfRange {
+        property: String,
+        value: i32,
+        min: i32,
+        max: i32,
+    },
+    /// The value falls between the steps the camera accepts for the property
+    OffStep {
+        property: String,
+        value: i32,
+        step: i32,
+        below: i32,
+        above: i32,
+    },
+    /// The camera does not report a default value for the property
+    NoDefault { property: String },
+    /// A property can't be restored because its earlier value wasn't known
+    NothingToRestore { property: String },
+    /// The backend failed to enumerate, read or write
+    Backend(anyhow::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CameraNotFound { index, count } => write!(
+                f,
+                "Camera index {} not found (only {} devices available)",
+                index, count
+            ),
+            Self::InvalidPropertyName(reason) => write!(f, "Invalid property name: {}", reason),
+            Self::PropertyNotFound { property, camera } => write!(
+                f,
+                "Property '{}' not found on device '{}'",
+                property, camera
+            ),
+            Self::InvalidValue { reason, .. } => f.write_str(reason),
+            Self::OutOfRange {
+                property,
+                value,
+                min,
+                max,
+            } => write!(
+                f,
+                "Value {} for property '{}' is outside the supported range [{}, {}]",
+                value, property, min, max
+            ),
+            Self::OffStep {
+                property,
+                value,
+                step,
+                below,
+                above,
+            } => write!(
+                f,
+                "Value {} for property '{}' falls between steps of {} (nearest valid values: {}, {})",
+                value, property, step, below, above
+            ),
+            Self::NoDefault { property } => {
+                write!(f, "Property '{}' has no default value", property)
+            }
+            Self::NothingToRestore { property } => {
+                write!(f, "Property '{}' had no known value to restore", property)
+            }
+            Self::Backend(e) => write!(f, "{:#}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<anyhow::Error> for Error {
+    fn from(e: anyhow::Error) -> Self {
+        Self::Backend(e)
+    }
+}
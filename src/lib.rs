@@ -0,0 +1,33 @@
+//! Read and write webcam properties (brightness, exposure, powerline frequency, ...)
+//!
+//! The entry point is [`Camera`]: open one with [`Camera::open`] or all of them with
+//! [`Camera::enumerate`], passing a [`backend::CameraBackend`]. Use
+//! [`backend::default_backend`] for the platform's native cameras (DirectShow on Windows,
+//! V4L2 on Linux) or [`backend::simulated::SimulatedBackend`] for fixture-driven cameras.
+//!
+//! Properties are addressed by their DirectShow names (`Brightness`, `Exposure`, ...) or
+//! aliases, as listed in the [`registry`].
+//! [`Camera::get`] reads a property, [`Camera::set`] writes a [`webcam::PropertyValue`],
+//! [`Camera::set_snapped`] rounds values between steps according to a [`Snap`] policy,
+//! and [`Camera::reset`] restores the camera's default. Writes made with
+//! [`Camera::apply_undoable`] are saved to an [`Undo`] log, so [`Camera::roll_back`] can put
+//! a half-applied batch back, and [`Camera::verify`] reads a write back to check the camera
+//! kept it. Failures come back as [`Error`].
+//! The [`webcam`] module holds the underlying device model and value formatting, and
+//! [`selector::Selector`] picks cameras by name, device path, USB ID, serial number or a
+//! saved [`alias`]. A [`profile::Profile`] snapshots cameras' settings to a file.
+pub mod alias;
+pub mod backend;
+mod camera;
+pub mod config;
+pub mod device_path;
+mod error;
+pub mod profile;
+pub mod registry;
+pub mod selector;
+#[cfg(test)]
+mod test_support;
+pub mod webcam;
+
+pub use camera::{Camera, DEFAULT_SETTLE, ReadBack, Resolved, Snap, Undo};
+pub use error::Error;
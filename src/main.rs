@@ -1,32 +1,58 @@
-pub mod webcam;
-
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use indexmap::IndexMap;
-use tracing::{debug, info, instrument};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+use tracing::{debug, info, instrument, warn};
 use tracing_subscriber::filter::LevelFilter;
 
+use wincamcfg::alias::{self, Aliases, CameraAlias};
+use wincamcfg::backend::ordered::{Order, OrderedBackend, SeenFile};
+use wincamcfg::backend::record::{RecordingBackend, ReplayBackend};
+use wincamcfg::backend::simulated::SimulatedBackend;
+use wincamcfg::backend::{self, CameraBackend};
+use wincamcfg::config::{Config, Scope};
+use wincamcfg::profile::{CameraProfile, Difference, Mode, Profile};
+use wincamcfg::registry::{Category, Unit};
+use wincamcfg::selector::{self, Selector, Term};
+use wincamcfg::webcam::{self, DeviceKind, PropertyValue};
+use wincamcfg::{Camera, Resolved, Snap, Undo};
+
 // Output structures for JSON/text rendering
 #[derive(Debug, serde::Serialize)]
 struct DeviceOutput<'a> {
     index: usize,
     name: &'a str,
+    /// Tells apart cameras with the same name, e.g. "serial A1B2C3D4" or "port 1.3"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    discriminator: Option<String>,
     properties: IndexMap<String, PropertyOutput>,
 }
 
-// Property output with formatted values (value, default, and supported_values are all formatted strings)
+// Property output with typed values (value and default) and formatted metadata strings
 #[derive(Debug, serde::Serialize)]
 struct PropertyOutput {
     #[serde(skip_serializing_if = "Option::is_none")]
-    value: Option<String>,
+    value: Option<PropertyValue>,
     #[serde(skip_serializing_if = "Option::is_none")]
     mode: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    default: Option<String>,
+    default: Option<PropertyValue>,
     #[serde(skip_serializing_if = "Option::is_none")]
     supported_values: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     modes_supported: Option<String>,
+    #[serde(flatten)]
+    description: Option<PropertyDescription>,
+}
+
+// Registry details shown by `get --describe`
+#[derive(Debug, serde::Serialize)]
+struct PropertyDescription {
+    category: Category,
+    unit: Unit,
+    description: &'static str,
 }
 
 #[derive(Debug, Clone, ValueEnum)]
@@ -35,14 +61,154 @@ enum OutputFormat {
     Json,
 }
 
+// How `set` handles values between a property's steps
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum SnapMode {
+    Nearest,
+    Down,
+    Up,
+    Error,
+}
+
+// How cameras are ordered, and so which index each one gets
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum DeviceOrder {
+    /// The order the system enumerates cameras in, which can change after a reboot
+    Enum,
+    /// Sorted by device path
+    Path,
+    /// The order cameras were first seen on this machine, remembered across runs
+    FirstSeen,
+}
+
+impl From<DeviceOrder> for Order {
+    fn from(order: DeviceOrder) -> Self {
+        match order {
+            DeviceOrder::Enum => Order::Enum,
+            DeviceOrder::Path => Order::Path,
+            DeviceOrder::FirstSeen => Order::FirstSeen,
+        }
+    }
+}
+
+impl From<SnapMode> for Snap {
+    fn from(mode: SnapMode) -> Self {
+        match mode {
+            SnapMode::Nearest => Snap::Nearest,
+            SnapMode::Down => Snap::Down,
+            SnapMode::Up => Snap::Up,
+            SnapMode::Error => Snap::Error,
+        }
+    }
+}
+
+// Alias output for `alias list`
+#[derive(serde::Serialize)]
+struct AliasOutput<'a> {
+    name: &'a str,
+    scope: Scope,
+    /// Index of the camera the alias currently resolves to, if it is connected
+    index: Option<usize>,
+    #[serde(flatten)]
+    alias: &'a CameraAlias,
+}
+
+// Outcome of writing one property
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Ok,
+    Failed,
+    /// Not attempted, e.g. the camera doesn't support the property
+    Skipped,
+    /// Dry run: would be written
+    Planned,
+    /// Dry run: the property already has the target value and mode
+    Unchanged,
+    /// Written, then restored to its earlier state because another write failed
+    #[serde(rename = "rolled_back")]
+    RolledBack,
+    /// Written, but restoring it to its earlier state failed
+    #[serde(rename = "rollback_failed")]
+    RollbackFailed,
+    /// Not written because an earlier write to the camera failed and --atomic is set
+    #[serde(rename = "not_attempted")]
+    NotAttempted,
+}
+
+// Differences found on one camera by `diff`
+#[derive(serde::Serialize)]
+struct DiffOutput<'a> {
+    index: usize,
+    name: &'a str,
+    /// Set when nothing was compared, e.g. no camera in the profile matches
+    #[serde(skip_serializing_if = "Option::is_none")]
+    note: Option<&'static str>,
+    differences: Vec<Difference>,
+}
+
 #[derive(serde::Serialize)]
 struct SetResult {
     index: usize,
     name: String,
     property: String,
-    value: String,
+    status: Status,
+    /// Value as given on the command line or in the profile, before it was resolved
+    requested: Option<PropertyValue>,
+    /// Value written, or to be written in a dry run
+    value: Option<PropertyValue>,
+    previous: Option<PropertyValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    previous_mode: Option<Mode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    snapped_from: Option<PropertyValue>,
+    /// Value read back from the camera after writing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    actual: Option<PropertyValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    actual_mode: Option<Mode>,
+    /// Whether the value and mode read back match what was written
+    #[serde(skip_serializing_if = "Option::is_none")]
+    verified: Option<bool>,
     success: bool,
     error: Option<String>,
+    /// Why the property was skipped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+}
+
+// What happened to one write, or would happen in a dry run
+struct WriteOutcome {
+    status: Status,
+    value: Option<PropertyValue>,
+    actual: Option<PropertyValue>,
+    actual_mode: Option<Mode>,
+    verified: Option<bool>,
+    error: Option<String>,
+}
+
+impl WriteOutcome {
+    fn new(status: Status, value: PropertyValue) -> Self {
+        Self {
+            status,
+            value: Some(value),
+            actual: None,
+            actual_mode: None,
+            verified: None,
+            error: None,
+        }
+    }
+
+    fn failed(error: wincamcfg::Error) -> Self {
+        Self {
+            status: Status::Failed,
+            value: None,
+            actual: None,
+            actual_mode: None,
+            verified: None,
+            error: Some(error.to_string()),
+        }
+    }
 }
 
 /// A command-line utility for managing webcam properties
@@ -53,10 +219,105 @@ struct SetResult {
     long_about = "A command line utility for managing webcam configuration on windows.\n\nConfigure camera properties like brightness, contrast, focus, exposure, and more using DirectShow APIs."
 )]
 struct Cli {
+    /// Use simulated cameras loaded from a JSON fixture file instead of real devices
+    #[arg(long, global = true, value_name = "FILE", conflicts_with = "replay")]
+    fixture: Option<PathBuf>,
+
+    /// Record every camera backend call and its result to a transcript file
+    #[arg(long, global = true, value_name = "FILE")]
+    record: Option<PathBuf>,
+
+    /// Replay camera backend calls from a transcript file made with --record
+    #[arg(long, global = true, value_name = "FILE")]
+    replay: Option<PathBuf>,
+
+    /// How cameras are ordered, which decides their indices
+    #[arg(long, global = true, value_enum, default_value_t = DeviceOrder::Enum)]
+    order: DeviceOrder,
+
+    #[command(flatten)]
+    filter: DeviceFilter,
+
     #[command(subcommand)]
     command: Commands,
 }
 
+/// Which cameras a command applies to
+#[derive(Args, Debug)]
+struct CameraSelection {
+    /// Camera index from list command (0-based), a range (1-3), "all", an @alias, or a selector:
+    /// name:<glob>, regex:<pattern>, path:<device path>, vidpid:<vid>:<pid> or serial:<serial>.
+    /// Combine with commas and exclude with "!" (e.g. "0,2" or "all,!2"); may be repeated
+    #[arg(short, long, required = true)]
+    camera: Vec<String>,
+
+    /// Allow a name, regex, vidpid or serial selector to match more than one camera
+    #[arg(long)]
+    allow_multiple: bool,
+
+    // Set from the global --exclude-virtual/--only-virtual flags
+    #[arg(skip)]
+    filter: DeviceFilter,
+}
+
+/// How `set` and `apply` write values
+#[derive(Args, Debug, Clone, Copy)]
+struct WriteOptions {
+    /// How to handle values that fall between the camera's steps
+    #[arg(long, value_enum, default_value_t = SnapMode::Error)]
+    snap: SnapMode,
+
+    /// Show the current and target value of every write without writing anything
+    #[arg(long)]
+    dry_run: bool,
+
+    /// If any write to a camera fails, put back every property already changed on it
+    #[arg(long)]
+    atomic: bool,
+
+    /// Milliseconds to wait after each write before reading the property back to verify it
+    /// [default: 50, or 0 with --fixture or --replay]
+    #[arg(long, value_name = "MS")]
+    settle: Option<u64>,
+
+    /// Treat a write as failed when the camera reports a different value or mode afterwards
+    #[arg(long)]
+    strict: bool,
+}
+
+/// Which kinds of camera commands see. Cameras keep their enumeration index either way.
+#[derive(Args, Debug, Clone, Copy, Default)]
+struct DeviceFilter {
+    /// Ignore virtual cameras (OBS, NVIDIA Broadcast, Teams and other software cameras)
+    #[arg(long, global = true, conflicts_with = "only_virtual")]
+    exclude_virtual: bool,
+
+    /// Only show virtual cameras
+    #[arg(long, global = true)]
+    only_virtual: bool,
+}
+
+impl DeviceFilter {
+    // Whether a camera of this kind passes the filter
+    fn allows(self, kind: DeviceKind) -> bool {
+        match kind {
+            DeviceKind::Physical => !self.only_virtual,
+            DeviceKind::Virtual => !self.exclude_virtual,
+        }
+    }
+
+    // The flag doing the filtering, for error messages
+    fn flag(self) -> Option<&'static str> {
+        if self.exclude_virtual {
+            Some("--exclude-virtual")
+        } else if self.only_virtual {
+            Some("--only-virtual")
+        } else {
+            None
+        }
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// List all video capture devices
@@ -65,6 +326,10 @@ enum Commands {
         #[arg(long)]
         include_device_path: bool,
 
+        /// Include bus, USB vendor/product ID, interface and serial number in text output
+        #[arg(long)]
+        include_hardware_ids: bool,
+
         /// Output format
         #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
         output: OutputFormat,
@@ -72,9 +337,16 @@ enum Commands {
 
     /// Get property values from camera(s)
     Get {
-        /// Camera index from list command (0-based), or "all" for all cameras
-        #[arg(short, long)]
-        camera: String,
+        #[command(flatten)]
+        selection: CameraSelection,
+
+        /// Include each property's category, unit and description
+        #[arg(long)]
+        describe: bool,
+
+        /// Show numeric values as a percentage of each property's range
+        #[arg(long)]
+        percent: bool,
 
         /// Output format
         #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
@@ -83,32 +355,135 @@ enum Commands {
 
     /// Set a property value on camera(s)
     Set {
-        /// Camera index from list command (0-based), or "all" for all cameras
-        #[arg(short, long)]
-        camera: String,
+        #[command(flatten)]
+        selection: CameraSelection,
 
         /// Property to set (e.g., PowerlineFrequency, Brightness, Contrast), or "all" to reset all properties (requires --default)
         #[arg(short, long)]
         property: String,
 
-        /// Value to set
+        /// Value to set. "+10" or "-2step" changes the current value; a plain negative
+        /// number is absolute
         #[arg(short, long, conflicts_with = "default", allow_hyphen_values = true)]
         value: Option<String>,
 
+        /// Change the current value by this amount (e.g. +10, -2, -2step), clamped to the range
+        #[arg(long, conflicts_with_all = ["value", "default"], allow_hyphen_values = true)]
+        delta: Option<String>,
+
         /// Set to default value
         #[arg(short, long, conflicts_with = "value")]
         default: bool,
 
+        #[command(flatten)]
+        write: WriteOptions,
+
         /// Output format
         #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
         output: OutputFormat,
     },
 
+    /// Save the settings of camera(s) to a profile file
+    Export {
+        #[command(flatten)]
+        selection: CameraSelection,
+
+        /// Profile file to write
+        #[arg(short, long, value_name = "FILE")]
+        file: PathBuf,
+    },
+
+    /// Write every property in a profile file to camera(s)
+    Apply {
+        #[command(flatten)]
+        selection: CameraSelection,
+
+        /// Profile file written by export
+        #[arg(short, long, value_name = "FILE")]
+        file: PathBuf,
+
+        #[command(flatten)]
+        write: WriteOptions,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+
+    /// Compare camera(s) against a profile or their defaults; exits 1 if anything differs
+    Diff {
+        #[command(flatten)]
+        selection: CameraSelection,
+
+        /// Profile file to compare against, or "defaults" for each camera's own defaults
+        #[arg(long, value_name = "FILE|defaults")]
+        against: String,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+
+    /// Check that every camera matching a profile complies with it, printing a one-line
+    /// summary; exits 0 if compliant, 1 if not and 2 if the check could not run, for
+    /// detection scripts
+    Check {
+        /// Profile file to check against
+        #[arg(long, value_name = "FILE")]
+        profile: PathBuf,
+    },
+
+    /// Manage camera aliases, used as --camera @name
+    Alias {
+        #[command(subcommand)]
+        command: AliasCommand,
+    },
+
     /// Show version information
     Version,
 }
 
-fn main() -> Result<()> {
+impl Commands {
+    // Commands that must not change anything on the machine, such as detection scripts
+    fn read_only(&self) -> bool {
+        matches!(self, Self::Check { .. } | Self::Diff { .. })
+    }
+}
+
+#[derive(Subcommand)]
+enum AliasCommand {
+    /// Save an alias for the selected camera
+    Add {
+        /// Alias name (letters, digits, '-' and '_')
+        name: String,
+
+        #[command(flatten)]
+        selection: CameraSelection,
+
+        /// Save to the machine-wide config instead of the user config
+        #[arg(long)]
+        machine: bool,
+    },
+
+    /// Remove an alias
+    Remove {
+        /// Alias name
+        name: String,
+
+        /// Remove from the machine-wide config instead of the user config
+        #[arg(long)]
+        machine: bool,
+    },
+
+    /// List saved aliases and the cameras they resolve to
+    List {
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+}
+
+fn main() -> Result<ExitCode> {
     let cli = Cli::parse();
 
     // Initialize tracing. RUST_LOG accepts a single level (trace/debug/info/warn/error/off).
@@ -127,22 +502,129 @@ fn main() -> Result<()> {
 
     debug!("Command: {:?}", std::env::args().collect::<Vec<_>>());
 
-    match cli.command {
+    if let Commands::Version = cli.command {
+        print_version();
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    // Detection scripts only see stdout and the exit code, so a check that can't run says
+    // why on stdout and exits with its own code rather than 1, which means non-compliant
+    let check = matches!(cli.command, Commands::Check { .. });
+    match execute(cli) {
+        Err(e) if check => {
+            println!("Error: {:#}", e);
+            Ok(ExitCode::from(CHECK_ERROR))
+        }
+        result => result,
+    }
+}
+
+// Open the backend the command line asks for and run the command against it
+fn execute(cli: Cli) -> Result<ExitCode> {
+    let backend: Box<dyn CameraBackend> = if let Some(path) = &cli.fixture {
+        Box::new(SimulatedBackend::from_file(path)?)
+    } else if let Some(path) = &cli.replay {
+        Box::new(ReplayBackend::from_file(path)?)
+    } else {
+        backend::default_backend()?
+    };
+
+    // Ordering wraps the recorder, so a transcript holds the raw enumeration order. Only
+    // real cameras use the first-seen order file, so simulated and replayed cameras are
+    // numbered the same on every machine, and read-only commands leave it alone.
+    let order = Order::from(cli.order);
+    let seen_file = if cli.fixture.is_some() || cli.replay.is_some() {
+        SeenFile::Ignore
+    } else if cli.command.read_only() {
+        SeenFile::Read
+    } else {
+        SeenFile::Update
+    };
+    let Some(record_path) = cli.record else {
+        let backend = OrderedBackend::new(backend.as_ref(), order).first_seen_file(seen_file);
+        return run(&backend, cli.filter, cli.command);
+    };
+
+    // Save the transcript even when the command fails; failures are what recordings are for
+    let recorder = RecordingBackend::new(backend);
+    let result = run(
+        &OrderedBackend::new(&recorder, order).first_seen_file(seen_file),
+        cli.filter,
+        cli.command,
+    );
+    recorder
+        .save(&record_path)
+        .context("Failed to save recording")?;
+    result
+}
+
+// Execute a subcommand against the chosen backend. Commands that report a finding (such as
+// differences) through the exit code return it; the rest exit 0 on success.
+fn run(backend: &dyn CameraBackend, filter: DeviceFilter, command: Commands) -> Result<ExitCode> {
+    match command {
         Commands::List {
             include_device_path,
+            include_hardware_ids,
+            output,
+        } => list_devices(
+            backend,
+            filter,
+            include_device_path,
+            include_hardware_ids,
             output,
-        } => list_devices(include_device_path, output)?,
-        Commands::Get { camera, output } => get_device_properties(camera, output)?,
+        )?,
+        Commands::Get {
+            mut selection,
+            describe,
+            percent,
+            output,
+        } => {
+            selection.filter = filter;
+            get_device_properties(backend, selection, describe, percent, output)?
+        }
         Commands::Version => print_version(),
+        Commands::Export {
+            mut selection,
+            file,
+        } => {
+            selection.filter = filter;
+            export_profile(backend, selection, &file)?
+        }
+        Commands::Apply {
+            mut selection,
+            file,
+            write,
+            output,
+        } => {
+            selection.filter = filter;
+            apply_profile(backend, selection, &file, write, output)?
+        }
+        Commands::Diff {
+            mut selection,
+            against,
+            output,
+        } => {
+            selection.filter = filter;
+            return diff_cameras(backend, selection, &against, output);
+        }
+        Commands::Check { profile } => return check_compliance(backend, filter, &profile),
+        Commands::Alias { command } => run_alias(backend, filter, command)?,
         Commands::Set {
-            camera,
+            mut selection,
             property,
             value,
+            delta,
             default,
+            write,
             output,
         } => {
+            let value = match (value, delta) {
+                (Some(v), _) => Some(v.parse::<PropertyValue>()?),
+                (None, Some(d)) => Some(PropertyValue::parse_delta(&d)?),
+                (None, None) => None,
+            };
             if !default && value.is_none() {
-                anyhow::bail!("Either --value or --default must be specified");
+                anyhow::bail!("Either --value, --delta or --default must be specified");
             }
 
             // Check if property is "all" - only allowed with --default
@@ -150,80 +632,260 @@ fn main() -> Result<()> {
                 anyhow::bail!("Property 'all' can only be used with --default flag");
             }
 
-            set_property(camera, property, value, default, output)?;
+            selection.filter = filter;
+            set_property(backend, selection, property, value, default, write, output)?;
         }
     }
 
-    Ok(())
+    Ok(ExitCode::SUCCESS)
 }
 
-/// Parse camera selection and return device indices
-fn parse_camera_selection(camera: &str, device_count: usize) -> Result<Vec<usize>> {
-    // Sanitize input: limit length
-    if camera.len() > 16 {
-        anyhow::bail!("Camera selection string exceeds maximum allowed length");
+// Execute an alias subcommand
+fn run_alias(
+    backend: &dyn CameraBackend,
+    filter: DeviceFilter,
+    command: AliasCommand,
+) -> Result<()> {
+    let scope = |machine: bool| if machine { Scope::Machine } else { Scope::User };
+    match command {
+        AliasCommand::Add {
+            name,
+            mut selection,
+            machine,
+        } => {
+            alias::validate_name(&name)?;
+            selection.filter = filter;
+            let cameras = Camera::enumerate(backend).context("Failed to enumerate devices")?;
+            let indices = parse_camera_selection(&selection, &cameras)?;
+            let &[idx] = indices.as_slice() else {
+                anyhow::bail!(
+                    "An alias must name exactly one camera, but the selection matches {}",
+                    indices.len()
+                );
+            };
+            let camera = &cameras[idx];
+            let alias = CameraAlias::new(camera.info());
+            if camera.device_path().is_none() {
+                anyhow::bail!("Camera '{}' has no device path to alias", camera.name());
+            }
+            // Composite cameras (most webcams) have no serial number in their device path
+            let path_only = alias.serial.is_none();
+            if path_only {
+                warn!(
+                    "Camera '{}' has no serial number in its device path; the alias will stop working if the path changes, e.g. when the camera is plugged into another USB port",
+                    camera.name()
+                );
+            }
+            alias::add(scope(machine), &name, alias)?;
+            println!(
+                "Saved @{} for [{}] {} in the {} config{}",
+                name.to_lowercase(),
+                idx,
+                camera.name(),
+                scope(machine),
+                if path_only {
+                    " (matched by device path only)"
+                } else {
+                    ""
+                }
+            );
+        }
+        AliasCommand::Remove { name, machine } => {
+            if !alias::remove(scope(machine), &name)? {
+                anyhow::bail!("No alias '{}' in the {} config", name, scope(machine));
+            }
+            println!("Removed @{} from the {} config", name, scope(machine));
+        }
+        AliasCommand::List { output } => {
+            let aliases = Aliases::load()?;
+            let devices = webcam::list_devices(backend)?;
+            let paths: Vec<Option<&str>> =
+                devices.iter().map(|d| d.device_path.as_deref()).collect();
+            // An alias is hidden along with its camera; aliases of cameras that aren't
+            // connected are classified by what they recorded
+            let outputs: Vec<AliasOutput> = aliases
+                .iter()
+                .map(|(name, scope, alias)| AliasOutput {
+                    name,
+                    scope,
+                    index: alias.resolve(paths.iter().copied()).first().copied(),
+                    alias,
+                })
+                .filter(|a| {
+                    let kind = a.index.map_or_else(
+                        || {
+                            DeviceKind::classify(
+                                a.alias.camera_name.as_deref(),
+                                a.alias.device_path.as_deref(),
+                                None,
+                            )
+                        },
+                        |idx| devices[idx].kind,
+                    );
+                    filter.allows(kind)
+                })
+                .collect();
+
+            match output {
+                OutputFormat::Json => println!("{}", render_json(&outputs)?),
+                OutputFormat::Text if outputs.is_empty() => {
+                    let path = Config::path(Scope::User).map_or_else(
+                        || "the config file".to_string(),
+                        |p| p.display().to_string(),
+                    );
+                    println!("No aliases saved in {}.", path);
+                }
+                OutputFormat::Text => {
+                    for a in &outputs {
+                        let camera = match a.index {
+                            Some(idx) => format!("[{}] {}", idx, devices[idx].name),
+                            None => "not connected".to_string(),
+                        };
+                        println!(
+                            "@{}: {} ({}, {})",
+                            a.name,
+                            camera,
+                            a.alias.target(),
+                            a.scope
+                        );
+                    }
+                }
+            }
+        }
     }
+    Ok(())
+}
 
-    if camera.eq_ignore_ascii_case("all") {
-        Ok((0..device_count).collect())
+/// Parse camera selection and return device indices, in the order they were selected.
+/// Cameras hidden by the filter are never selected.
+fn parse_camera_selection(selection: &CameraSelection, cameras: &[Camera]) -> Result<Vec<usize>> {
+    let filter = selection.filter;
+    // Only read the config files when the selection uses an alias
+    let aliases = if selection.camera.iter().any(|c| c.contains('@')) {
+        Aliases::load()?
     } else {
-        // Only allow digits for camera index
-        if !camera.chars().all(|c| c.is_ascii_digit()) {
-            anyhow::bail!("Invalid camera index: must be a number or 'all'");
-        }
+        Aliases::default()
+    };
+    let terms = selector::parse_selection(&selection.camera, &aliases)?;
+    let devices: Vec<_> = cameras.iter().map(Camera::info).collect();
 
-        let idx: usize = camera
-            .parse()
-            .with_context(|| format!("Invalid camera index: {}", camera))?;
+    // A selection that starts with an exclusion ("!2") excludes from every camera
+    let mut indices: Vec<usize> = match terms.first() {
+        Some(Term::Exclude(_)) => (0..cameras.len())
+            .filter(|&idx| filter.allows(cameras[idx].kind()))
+            .collect(),
+        _ => Vec::new(),
+    };
 
-        if idx >= device_count {
-            anyhow::bail!(
-                "Camera index {} not found (only {} devices available)",
-                idx,
-                device_count
-            );
+    for term in &terms {
+        match term {
+            Term::Include(selector) => {
+                let mut matched = selector.select(devices.iter().copied())?;
+                let before = matched.len();
+                matched.retain(|&idx| filter.allows(cameras[idx].kind()));
+                if matched.is_empty()
+                    && before > 0
+                    && !matches!(selector, Selector::All)
+                    && let Some(flag) = filter.flag()
+                {
+                    anyhow::bail!("'{}' only matches cameras hidden by {}", selector, flag);
+                }
+                check_multiple(selector, &matched, cameras, selection.allow_multiple)?;
+                for idx in matched {
+                    if !indices.contains(&idx) {
+                        indices.push(idx);
+                    }
+                }
+            }
+            Term::Exclude(selector) => {
+                let excluded = selector.matching(devices.iter().copied());
+                indices.retain(|idx| !excluded.contains(idx));
+            }
         }
+    }
 
-        Ok(vec![idx])
+    Ok(indices)
+}
+
+// A name or model pattern that unexpectedly matches several cameras is more likely a
+// mistake than a request to change them all
+fn check_multiple(
+    selector: &Selector,
+    matched: &[usize],
+    cameras: &[Camera],
+    allow_multiple: bool,
+) -> Result<()> {
+    if matched.len() <= 1
+        || allow_multiple
+        || matches!(selector, Selector::All | Selector::Range { .. })
+    {
+        return Ok(());
     }
+    let names = matched
+        .iter()
+        .map(|&i| format!("[{}] {}", i, cameras[i].name()))
+        .collect::<Vec<_>>()
+        .join(", ");
+    anyhow::bail!(
+        "'{}' matches {} cameras ({}); pass --allow-multiple to select all of them",
+        selector,
+        matched.len(),
+        names
+    )
 }
 
 // Build device output structure from domain DeviceInfo
 // Converts property vectors to IndexMap with formatted values
-fn build_device_output<'a>(idx: usize, device: &'a webcam::DeviceInfo) -> DeviceOutput<'a> {
+fn build_device_output<'a>(
+    camera: &'a Camera,
+    discriminator: Option<String>,
+    describe: bool,
+    percent: bool,
+) -> DeviceOutput<'a> {
     // Collect all properties from both VideoProcAmp and CameraControl
-    let property_outputs: IndexMap<String, PropertyOutput> = device
-        .video_proc_amp_properties
-        .iter()
-        .chain(&device.camera_control_properties)
+    let property_outputs: IndexMap<String, PropertyOutput> = camera
+        .properties()
         .map(|prop| {
+            // Labelled values stay as labels; a percentage of "50Hz" means nothing
+            let format = |v: i32| {
+                let labelled = prop.definition().is_some_and(|d| !d.labels.is_empty());
+                percent
+                    .then(|| PropertyValue::percent_of_range(prop, v))
+                    .flatten()
+                    .filter(|_| !labelled)
+                    .unwrap_or_else(|| PropertyValue::from_raw(&prop.name, v))
+            };
             (
                 prop.name.clone(),
                 PropertyOutput {
-                    value: prop
-                        .current
-                        .map(|v| webcam::format_property_value(&prop.name, v)),
+                    value: prop.current.map(format),
                     mode: prop
                         .caps
                         .zip(prop.current_flags)
                         .and_then(|(caps, flags)| webcam::current_mode(caps, flags))
                         .map(String::from),
-                    default: prop
-                        .default
-                        .map(|v| webcam::format_property_value(&prop.name, v)),
+                    default: prop.default.map(format),
                     supported_values: prop
                         .min
                         .zip(prop.max)
                         .and_then(|(min, max)| webcam::build_enum_display(&prop.name, min, max)),
                     modes_supported: prop.capabilities.clone(),
+                    description: prop.definition().filter(|_| describe).map(|def| {
+                        PropertyDescription {
+                            category: def.category,
+                            unit: def.unit,
+                            description: def.description,
+                        }
+                    }),
                 },
             )
         })
         .collect();
 
     DeviceOutput {
-        index: idx,
-        name: device.name.as_deref().unwrap_or("Unknown"),
+        index: camera.index(),
+        name: camera.name(),
+        discriminator,
         properties: property_outputs,
     }
 }
@@ -237,7 +899,12 @@ fn render_json<T: serde::Serialize>(value: &T) -> Result<String> {
 // Shows properties with formatted values
 fn render_text(outputs: &[DeviceOutput]) {
     for output in outputs {
-        println!("[{}] {}", output.index, output.name);
+        match output.discriminator {
+            Some(ref discriminator) => {
+                println!("[{}] {} ({})", output.index, output.name, discriminator)
+            }
+            None => println!("[{}] {}", output.index, output.name),
+        }
         println!("  Properties:");
 
         if output.properties.is_empty() {
@@ -245,8 +912,14 @@ fn render_text(outputs: &[DeviceOutput]) {
         } else {
             for (name, prop) in &output.properties {
                 print!("    {}: ", name);
-                display_property_value(prop);
+                display_property_value(name, prop);
                 println!();
+                if let Some(ref d) = prop.description {
+                    match d.unit {
+                        Unit::None => println!("      {}: {}", d.category, d.description),
+                        unit => println!("      {}: {} ({})", d.category, d.description, unit),
+                    }
+                }
             }
         }
 
@@ -255,15 +928,15 @@ fn render_text(outputs: &[DeviceOutput]) {
 }
 
 // Display a single property value with metadata (Supported values and Default)
-fn display_property_value(prop: &PropertyOutput) {
+fn display_property_value(name: &str, prop: &PropertyOutput) {
     let Some(ref current) = prop.value else {
         print!("<unavailable>");
         return;
     };
 
-    // Display the value (already formatted), tagging the current mode when
+    // Display the value with its label or unit, tagging the current mode when
     // the property supports both Auto and Manual.
-    print!("{}", current);
+    print!("{}", webcam::format_value(name, current));
     if let Some(ref mode) = prop.mode {
         print!(" [{}]", mode);
     }
@@ -279,19 +952,47 @@ fn display_property_value(prop: &PropertyOutput) {
         meta.push(format!("Modes: {}", modes));
     }
     if let Some(ref default) = prop.default {
-        meta.push(format!("Default: {}", default));
+        meta.push(format!("Default: {}", webcam::format_value(name, default)));
     }
     if !meta.is_empty() {
         print!(" ({})", meta.join(", "));
     }
 }
 
-#[instrument(skip(output))]
-fn list_devices(include_device_path: bool, output: OutputFormat) -> Result<()> {
-    debug!(include_device_path, output_format = ?output, "Listing devices");
+#[instrument(skip(backend, output))]
+fn list_devices(
+    backend: &dyn CameraBackend,
+    filter: DeviceFilter,
+    include_device_path: bool,
+    include_hardware_ids: bool,
+    output: OutputFormat,
+) -> Result<()> {
+    debug!(?filter, include_device_path, include_hardware_ids, output_format = ?output, "Listing devices");
 
     // Get simple device list (index and name only)
-    let devices = webcam::list_devices()?;
+    let mut devices = webcam::list_devices(backend)?;
+
+    // A broken config file shouldn't stop cameras being listed
+    let aliases = Aliases::load().unwrap_or_else(|e| {
+        warn!("Failed to load camera aliases: {:#}", e);
+        Aliases::default()
+    });
+    let names: Vec<Vec<String>> = {
+        let paths: Vec<Option<&str>> = devices.iter().map(|d| d.device_path.as_deref()).collect();
+        (0..devices.len())
+            .map(|idx| {
+                aliases
+                    .names_for(&paths, idx)
+                    .into_iter()
+                    .map(String::from)
+                    .collect()
+            })
+            .collect()
+    };
+    for (device, names) in devices.iter_mut().zip(names) {
+        device.aliases = names;
+    }
+    devices.retain(|device| filter.allows(device.kind));
 
     info!("Found {} device(s)", devices.len());
 
@@ -305,16 +1006,22 @@ fn list_devices(include_device_path: bool, output: OutputFormat) -> Result<()> {
                 println!("No video capture devices found.");
             } else {
                 for device in devices {
-                    let suffix = if include_device_path {
-                        device
-                            .device_path
-                            .as_deref()
-                            .map(|p| format!(" ({})", p))
-                            .unwrap_or_default()
-                    } else {
-                        String::new()
-                    };
-                    println!("[{}] {}{}", device.index, device.name, suffix);
+                    let mut suffix = String::new();
+                    if device.kind == DeviceKind::Virtual {
+                        suffix.push_str(" (virtual)");
+                    }
+                    if include_device_path && let Some(ref path) = device.device_path {
+                        suffix.push_str(&format!(" ({})", path));
+                    }
+                    if include_hardware_ids && let Some(ref hardware) = device.hardware {
+                        suffix.push_str(&format!(" [{}]", hardware));
+                    }
+                    let aliases: String = device
+                        .aliases
+                        .iter()
+                        .map(|alias| format!(" @{}", alias))
+                        .collect();
+                    println!("[{}]{} {}{}", device.index, aliases, device.name, suffix);
                 }
             }
         }
@@ -323,17 +1030,27 @@ fn list_devices(include_device_path: bool, output: OutputFormat) -> Result<()> {
     Ok(())
 }
 
-#[instrument(skip(output))]
-fn get_device_properties(camera: String, output: OutputFormat) -> Result<()> {
-    debug!(camera = %camera, output_format = ?output, "Getting device properties");
+#[instrument(skip(backend, output))]
+fn get_device_properties(
+    backend: &dyn CameraBackend,
+    selection: CameraSelection,
+    describe: bool,
+    percent: bool,
+    output: OutputFormat,
+) -> Result<()> {
+    debug!(?selection, describe, percent, output_format = ?output, "Getting device properties");
 
-    let devices = webcam::enumerate_devices().context("Failed to enumerate devices")?;
+    let cameras = Camera::enumerate(backend).context("Failed to enumerate devices")?;
 
-    let indices = parse_camera_selection(&camera, devices.len())?;
+    let indices = parse_camera_selection(&selection, &cameras)?;
 
+    // Discriminators are worked out across every camera, so they don't depend on the selection
+    let mut discriminators = webcam::discriminators(cameras.iter().map(Camera::info));
     let outputs: Vec<DeviceOutput> = indices
         .iter()
-        .map(|&idx| build_device_output(idx, &devices[idx]))
+        .map(|&idx| {
+            build_device_output(&cameras[idx], discriminators[idx].take(), describe, percent)
+        })
         .collect();
 
     match output {
@@ -344,19 +1061,21 @@ fn get_device_properties(camera: String, output: OutputFormat) -> Result<()> {
     Ok(())
 }
 
-#[instrument(skip(output))]
+#[instrument(skip(backend, output))]
 fn set_property(
-    camera: String,
+    backend: &dyn CameraBackend,
+    selection: CameraSelection,
     property: String,
-    value: Option<String>,
+    value: Option<PropertyValue>,
     use_default: bool,
+    options: WriteOptions,
     output: OutputFormat,
 ) -> Result<()> {
-    debug!(camera = %camera, property = %property, value = ?value, use_default, output_format = ?output, "Setting property");
+    debug!(?selection, property = %property, value = ?value, use_default, ?options, output_format = ?output, "Setting property");
 
-    let devices = webcam::enumerate_devices().context("Failed to enumerate devices")?;
+    let mut cameras = Camera::enumerate(backend).context("Failed to enumerate devices")?;
 
-    let indices = parse_camera_selection(&camera, devices.len())?;
+    let indices = parse_camera_selection(&selection, &cameras)?;
 
     // Check if we're resetting all properties
     let reset_all = property.eq_ignore_ascii_case("all");
@@ -364,100 +1083,581 @@ fn set_property(
     let mut results: Vec<SetResult> = Vec::new();
 
     for &idx in &indices {
-        let device = &devices[idx];
-        let device_name = device.name.as_deref().unwrap_or("Unknown");
+        let camera = &mut cameras[idx];
 
         // Get list of properties to set
-        let properties_to_set: Vec<(&str, String)> = if reset_all {
-            // Collect all properties with their default values, formatted as strings
-            device
-                .video_proc_amp_properties
-                .iter()
-                .chain(&device.camera_control_properties)
-                .map(|p| {
-                    let val = p
-                        .default
-                        .map(|v| webcam::format_property_value(&p.name, v))
-                        .unwrap_or_else(|| "".to_string());
-                    (p.name.as_str(), val)
-                })
-                .collect()
+        let properties_to_set: Vec<String> = if reset_all {
+            camera.properties().map(|p| p.name.clone()).collect()
         } else {
-            // Single property
-            let value_to_set = if use_default {
-                // Find the property to get its default value, formatted as string
-                device
-                    .video_proc_amp_properties
-                    .iter()
-                    .chain(&device.camera_control_properties)
-                    .find(|p| p.name.eq_ignore_ascii_case(&property))
-                    .map(|p| {
-                        p.default
-                            .map(|v| webcam::format_property_value(&p.name, v))
-                            .unwrap_or_else(|| "".to_string())
-                    })
-                    .ok_or_else(|| {
-                        anyhow::anyhow!(
-                            "Property '{}' not found on device '{}'",
-                            property,
-                            device_name
-                        )
-                    })?
-            } else {
-                value.clone().unwrap() // Safe because we validated earlier
-            };
-            vec![(property.as_str(), value_to_set)]
+            vec![property.clone()]
         };
 
+        // Results of this camera's writes start here, and `undo` holds the state of each
+        // property changed so far, for --atomic
+        let start = results.len();
+        let mut undo = Undo::new();
+        let mut stopped = false;
+
         // Set each property
-        for (prop_name, prop_value) in properties_to_set {
-            let result = webcam::set_property(device, prop_name, &prop_value);
+        for prop_name in properties_to_set {
+            let (previous, previous_mode) = camera.property(&prop_name).map_or((None, None), |p| {
+                let current = p.current.map(|v| PropertyValue::from_raw(&p.name, v));
+                (current, Mode::of(p))
+            });
+            // Report the canonical name when the property was given by alias
+            let canonical = camera
+                .property(&prop_name)
+                .map_or(prop_name.clone(), |p| p.name.clone());
+
+            if stopped {
+                results.push(not_attempted(camera, canonical, value.clone()));
+                continue;
+            }
+            let resolved = match &value {
+                Some(v) => camera.resolve(&prop_name, v, options.snap.into()),
+                None => camera.resolve_default(&prop_name),
+            };
+            let snapped_from = resolved.as_ref().ok().and_then(|r| r.snapped_from);
+            let outcome = write_or_plan(camera, &prop_name, resolved, options, &mut undo);
+
+            if let Some(e) = &outcome.error {
+                debug!(device_index = idx, device_name = camera.name(), property = %prop_name, error = %e, "Failed to set property");
+            }
+            stopped = options.atomic && outcome.status == Status::Failed;
+            results.push(SetResult {
+                index: idx,
+                name: camera.name().to_string(),
+                status: outcome.status,
+                requested: value.clone().or_else(|| outcome.value.clone()),
+                value: outcome.value.or_else(|| value.clone()),
+                previous,
+                previous_mode,
+                snapped_from: snapped_from.map(|v| PropertyValue::from_raw(&canonical, v)),
+                actual: outcome.actual,
+                actual_mode: outcome.actual_mode,
+                verified: outcome.verified,
+                property: canonical,
+                success: outcome.error.is_none(),
+                error: outcome.error,
+                reason: None,
+            });
+        }
+
+        if stopped {
+            roll_back(camera, &mut results[start..], undo);
+        }
+    }
+
+    // Relative changes also show the value they started from
+    let relative = matches!(
+        value,
+        Some(PropertyValue::Relative(_) | PropertyValue::RelativeSteps(_))
+    );
+
+    match output {
+        OutputFormat::Text => render_set_results(&results, relative, options.dry_run),
+        OutputFormat::Json => println!("{}", render_json(&results)?),
+    }
+
+    Ok(())
+}
 
-            match &result {
-                Ok(_) => {
-                    info!(device_index = idx, device_name, property = %prop_name, value = %prop_value, "Property set successfully")
+// Write a resolved value and read it back to check the camera kept it. In a dry run
+// nothing is written; the outcome instead says whether the write would change the property.
+fn write_or_plan(
+    camera: &mut Camera,
+    property: &str,
+    resolved: Result<Resolved, wincamcfg::Error>,
+    options: WriteOptions,
+    undo: &mut Undo,
+) -> WriteOutcome {
+    let resolved = match resolved {
+        Ok(resolved) => resolved,
+        Err(e) => return WriteOutcome::failed(e),
+    };
+
+    if options.dry_run {
+        let unchanged = camera.property(property).is_ok_and(|p| {
+            let mode = Mode::of(p);
+            match resolved.raw {
+                Some(raw) => p.current == Some(raw) && mode != Some(Mode::Auto),
+                None => mode == Some(Mode::Auto),
+            }
+        });
+        let status = if unchanged {
+            Status::Unchanged
+        } else {
+            Status::Planned
+        };
+        return WriteOutcome::new(status, resolved.value);
+    }
+
+    let mut outcome = match camera.apply_undoable(property, &resolved, undo) {
+        Ok(written) => WriteOutcome::new(Status::Ok, written),
+        Err(e) => return WriteOutcome::failed(e),
+    };
+
+    let settle = options.settle.map(Duration::from_millis);
+    let mismatch = match camera.verify(property, &resolved, settle) {
+        Ok(read_back) => {
+            outcome.actual = read_back.value;
+            outcome.actual_mode = read_back.mode;
+            outcome.verified = Some(read_back.matches);
+            (!read_back.matches).then(|| {
+                let name = camera.property(property).map_or(property, |p| &p.name);
+                format!(
+                    "Camera reports {} after the write",
+                    format_state(name, outcome.actual.as_ref(), outcome.actual_mode)
+                )
+            })
+        }
+        Err(e) => {
+            warn!(device_index = camera.index(), property, error = %e, "Failed to read back property");
+            outcome.verified = Some(false);
+            Some(format!("Failed to read the value back: {}", e))
+        }
+    };
+    if options.strict
+        && let Some(mismatch) = mismatch
+    {
+        outcome.status = Status::Failed;
+        outcome.error = Some(mismatch);
+    }
+    outcome
+}
+
+// For --atomic, after one of a camera's writes failed: restore the properties it did change,
+// newest first. `results` are that camera's results. Writes that failed --strict verification
+// are restored too, but keep their failed status.
+fn roll_back(camera: &mut Camera, results: &mut [SetResult], undo: Undo) {
+    for (property, restored) in camera.roll_back(undo) {
+        // Each property is written at most once per camera
+        let Some(result) = results.iter_mut().find(|r| r.property == property) else {
+            continue;
+        };
+        match restored {
+            Ok(_) if result.status == Status::Ok => result.status = Status::RolledBack,
+            Ok(_) => {}
+            Err(e) => {
+                result.status = Status::RollbackFailed;
+                result.error = Some(e.to_string());
+            }
+        }
+        result.success = false;
+    }
+}
+
+// Result for a property left alone because an earlier write to the camera failed (--atomic)
+fn not_attempted(camera: &Camera, property: String, requested: Option<PropertyValue>) -> SetResult {
+    SetResult {
+        index: camera.index(),
+        name: camera.name().to_string(),
+        property,
+        status: Status::NotAttempted,
+        requested,
+        value: None,
+        previous: None,
+        previous_mode: None,
+        snapped_from: None,
+        actual: None,
+        actual_mode: None,
+        verified: None,
+        success: false,
+        error: None,
+        reason: Some("an earlier write to this camera failed".to_string()),
+    }
+}
+
+// Render set/apply results as human-readable text, one line per property. `show_previous`
+// adds the value each property had before; a dry run shows it for every planned write.
+fn render_set_results(results: &[SetResult], show_previous: bool, dry_run: bool) {
+    for r in results {
+        match (r.status, &r.value) {
+            (Status::Ok, Some(value)) => {
+                let mut notes = Vec::new();
+                if let Some(ref from) = r.snapped_from {
+                    notes.push(format!(
+                        "snapped from {}",
+                        webcam::format_value(&r.property, from)
+                    ));
                 }
-                Err(e) => {
-                    debug!(device_index = idx, device_name, property = %prop_name, error = %e, "Failed to set property")
+                if show_previous && let Some(ref previous) = r.previous {
+                    notes.push(format!(
+                        "was {}",
+                        webcam::format_value(&r.property, previous)
+                    ));
                 }
+                if r.verified == Some(false) {
+                    // Nothing read back means the read itself failed
+                    notes.push(if r.actual.is_none() && r.actual_mode.is_none() {
+                        "not verified".to_string()
+                    } else {
+                        format!(
+                            "but camera reports {}",
+                            format_state(&r.property, r.actual.as_ref(), r.actual_mode)
+                        )
+                    });
+                }
+                let notes = if notes.is_empty() {
+                    String::new()
+                } else {
+                    format!(" ({})", notes.join(", "))
+                };
+                println!(
+                    "[{}] {}: {} set to {}{}",
+                    r.index,
+                    r.name,
+                    r.property,
+                    webcam::format_value(&r.property, value),
+                    notes
+                );
+            }
+            (Status::Planned, Some(value)) => {
+                let snapped = r.snapped_from.as_ref().map_or(String::new(), |from| {
+                    format!(
+                        " (snapped from {})",
+                        webcam::format_value(&r.property, from)
+                    )
+                });
+                println!(
+                    "[{}] {}: {}: {} -> {}{}",
+                    r.index,
+                    r.name,
+                    r.property,
+                    format_state(&r.property, r.previous.as_ref(), r.previous_mode),
+                    webcam::format_value(&r.property, value),
+                    snapped
+                );
+            }
+            (Status::Unchanged, Some(value)) => println!(
+                "[{}] {}: {}: already {}",
+                r.index,
+                r.name,
+                r.property,
+                webcam::format_value(&r.property, value)
+            ),
+            (Status::RolledBack, _) => println!(
+                "[{}] {}: {} rolled back to {}",
+                r.index,
+                r.name,
+                r.property,
+                format_state(&r.property, r.previous.as_ref(), r.previous_mode)
+            ),
+            (Status::RollbackFailed, _) => println!(
+                "[{}] {}: Failed to roll back {} - {}",
+                r.index,
+                r.name,
+                r.property,
+                r.error.as_deref().unwrap_or("Unknown error")
+            ),
+            (Status::NotAttempted, _) => println!(
+                "[{}] {}: {} not attempted - {}",
+                r.index,
+                r.name,
+                r.property,
+                r.reason.as_deref().unwrap_or("an earlier write failed")
+            ),
+            (Status::Skipped, _) => println!(
+                "[{}] {}: Skipped {} - {}",
+                r.index,
+                r.name,
+                r.property,
+                r.reason.as_deref().unwrap_or("not applicable")
+            ),
+            _ => println!(
+                "[{}] {}: {} {} - {}",
+                r.index,
+                r.name,
+                if dry_run {
+                    "Cannot set"
+                } else {
+                    "Failed to set"
+                },
+                r.property,
+                r.error.as_deref().unwrap_or("Unknown error")
+            ),
+        }
+    }
+    if dry_run {
+        println!("Dry run: nothing was written");
+    }
+}
+
+#[instrument(skip(backend, output))]
+fn apply_profile(
+    backend: &dyn CameraBackend,
+    selection: CameraSelection,
+    file: &std::path::Path,
+    options: WriteOptions,
+    output: OutputFormat,
+) -> Result<()> {
+    debug!(?selection, ?options, output_format = ?output, "Applying profile");
+
+    let profile = Profile::load(file)?;
+    let mut cameras = Camera::enumerate(backend).context("Failed to enumerate devices")?;
+    let indices = parse_camera_selection(&selection, &cameras)?;
+
+    let mut results: Vec<SetResult> = Vec::new();
+    for &idx in &indices {
+        let camera = &mut cameras[idx];
+        let skip = |camera: &Camera, property: &str, reason: &str| SetResult {
+            index: idx,
+            name: camera.name().to_string(),
+            property: property.to_string(),
+            status: Status::Skipped,
+            requested: None,
+            value: None,
+            previous: None,
+            previous_mode: None,
+            snapped_from: None,
+            actual: None,
+            actual_mode: None,
+            verified: None,
+            success: false,
+            error: None,
+            reason: Some(reason.to_string()),
+        };
+
+        let Some(settings) = profile.for_camera(camera) else {
+            results.push(skip(camera, "all", "no camera in the profile matches"));
+            continue;
+        };
+        debug!(device_index = idx, profile_camera = %settings.name, "Matched profile camera");
+
+        // As in set_property: where this camera's results start, and what to restore
+        let start = results.len();
+        let mut undo = Undo::new();
+        let mut stopped = false;
+
+        for (property, setting) in settings.write_order() {
+            let Ok(p) = camera.property(property) else {
+                results.push(skip(camera, property, "not supported by this camera"));
+                continue;
+            };
+            if stopped {
+                let canonical = p.name.clone();
+                results.push(not_attempted(camera, canonical, Some(setting.target())));
+                continue;
             }
+            let canonical = p.name.clone();
+            let previous = p.current.map(|v| PropertyValue::from_raw(&canonical, v));
+            let previous_mode = Mode::of(p);
+
+            let value = setting.target();
+            let resolved = camera.resolve(property, &value, options.snap.into());
+            let snapped_from = resolved.as_ref().ok().and_then(|r| r.snapped_from);
+            let outcome = write_or_plan(camera, property, resolved, options, &mut undo);
+            if let Some(e) = &outcome.error {
+                debug!(device_index = idx, device_name = camera.name(), property = %canonical, error = %e, "Failed to apply property");
+            }
+            stopped = options.atomic && outcome.status == Status::Failed;
 
             results.push(SetResult {
                 index: idx,
-                name: device_name.to_string(),
-                property: prop_name.to_string(),
-                value: prop_value,
-                success: result.is_ok(),
-                error: result.err().map(|e| e.to_string()),
+                name: camera.name().to_string(),
+                status: outcome.status,
+                requested: Some(value.clone()),
+                value: Some(outcome.value.unwrap_or(value)),
+                previous,
+                previous_mode,
+                snapped_from: snapped_from.map(|v| PropertyValue::from_raw(&canonical, v)),
+                actual: outcome.actual,
+                actual_mode: outcome.actual_mode,
+                verified: outcome.verified,
+                property: canonical,
+                success: outcome.error.is_none(),
+                error: outcome.error,
+                reason: None,
             });
         }
+
+        if stopped {
+            roll_back(camera, &mut results[start..], undo);
+        }
     }
 
-    // Output results
     match output {
+        OutputFormat::Text => render_set_results(&results, false, options.dry_run),
+        OutputFormat::Json => println!("{}", render_json(&results)?),
+    }
+
+    Ok(())
+}
+
+#[instrument(skip(backend, output))]
+fn diff_cameras(
+    backend: &dyn CameraBackend,
+    selection: CameraSelection,
+    against: &str,
+    output: OutputFormat,
+) -> Result<ExitCode> {
+    debug!(?selection, against, output_format = ?output, "Comparing cameras");
+
+    let profile = if against.eq_ignore_ascii_case("defaults") {
+        None
+    } else {
+        Some(Profile::load(std::path::Path::new(against))?)
+    };
+    let cameras = Camera::enumerate(backend).context("Failed to enumerate devices")?;
+    let indices = parse_camera_selection(&selection, &cameras)?;
+
+    let outputs: Vec<DiffOutput> = indices
+        .iter()
+        .map(|&idx| {
+            let camera = &cameras[idx];
+            let expected = match profile {
+                Some(ref profile) => profile.matching(camera).cloned(),
+                None => Some(CameraProfile::defaults(camera)),
+            };
+            DiffOutput {
+                index: idx,
+                name: camera.name(),
+                note: expected
+                    .is_none()
+                    .then_some("no camera in the profile matches"),
+                differences: expected.map_or_else(Vec::new, |e| e.compare(camera)),
+            }
+        })
+        .collect();
+
+    match output {
+        OutputFormat::Json => println!("{}", render_json(&outputs)?),
         OutputFormat::Text => {
-            for r in &results {
-                if r.success {
-                    println!(
-                        "[{}] {}: {} set to {}",
-                        r.index, r.name, r.property, r.value
-                    );
-                } else {
+            for output in &outputs {
+                println!("[{}] {}", output.index, output.name);
+                if let Some(note) = output.note {
+                    println!("    Not compared: {}", note);
+                } else if output.differences.is_empty() {
+                    println!("    No differences");
+                }
+                for d in &output.differences {
                     println!(
-                        "[{}] {}: Failed to set {} - {}",
-                        r.index,
-                        r.name,
-                        r.property,
-                        r.error.as_deref().unwrap_or("Unknown error")
+                        "    {}: expected {}, actual {}",
+                        d.property,
+                        format_state(&d.property, Some(&d.expected), d.expected_mode),
+                        format_state(&d.property, d.actual.as_ref(), d.actual_mode)
                     );
                 }
             }
         }
-        OutputFormat::Json => {
-            println!("{}", render_json(&results)?);
+    }
+
+    let differs = outputs.iter().any(|o| !o.differences.is_empty());
+    Ok(if differs {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+// Exit code of `check` when the check itself fails, e.g. the profile can't be read
+const CHECK_ERROR: u8 = 2;
+
+#[instrument(skip(backend))]
+fn check_compliance(
+    backend: &dyn CameraBackend,
+    filter: DeviceFilter,
+    file: &std::path::Path,
+) -> Result<ExitCode> {
+    debug!(?filter, "Checking compliance");
+
+    let profile = Profile::load(file)?;
+    let cameras = Camera::enumerate(backend).context("Failed to enumerate devices")?;
+
+    // Cameras the profile has no entry for, and profile cameras that aren't plugged in,
+    // are outside the check rather than failures
+    let mut checked = 0;
+    let mut failures = Vec::new();
+    for (idx, camera) in cameras.iter().enumerate() {
+        if !filter.allows(camera.kind()) {
+            continue;
+        }
+        let Some(expected) = profile.matching(camera) else {
+            debug!(
+                device_index = idx,
+                device_name = camera.name(),
+                "No matching camera in profile"
+            );
+            continue;
+        };
+        checked += 1;
+        let differences = expected.compare(camera);
+        if !differences.is_empty() {
+            let details = differences
+                .iter()
+                .map(|d| {
+                    format!(
+                        "{} {} (expected {})",
+                        d.property,
+                        format_state(&d.property, d.actual.as_ref(), d.actual_mode),
+                        format_state(&d.property, Some(&d.expected), d.expected_mode)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            failures.push(format!("[{}] {}: {}", idx, camera.name(), details));
         }
     }
 
+    let profile_name = file.display();
+    if failures.is_empty() {
+        match checked {
+            0 => println!(
+                "Compliant: no cameras matching {} are present",
+                profile_name
+            ),
+            1 => println!("Compliant: 1 camera matches {}", profile_name),
+            n => println!("Compliant: {} cameras match {}", n, profile_name),
+        }
+        Ok(ExitCode::SUCCESS)
+    } else {
+        println!(
+            "Non-compliant: {} of {} cameras differ from {}: {}",
+            failures.len(),
+            checked,
+            profile_name,
+            failures.join("; ")
+        );
+        Ok(ExitCode::FAILURE)
+    }
+}
+
+// Format a value and mode for diff output, e.g. "-5 (1/32s) [Manual]" or "Auto"
+fn format_state(property: &str, value: Option<&PropertyValue>, mode: Option<Mode>) -> String {
+    match (value, mode) {
+        (_, Some(Mode::Auto)) => "Auto".to_string(),
+        (Some(value), Some(mode)) => {
+            format!("{} [{}]", webcam::format_value(property, value), mode)
+        }
+        (Some(value), None) => webcam::format_value(property, value),
+        (None, Some(mode)) => mode.to_string(),
+        (None, None) => "<unavailable>".to_string(),
+    }
+}
+
+#[instrument(skip(backend))]
+fn export_profile(
+    backend: &dyn CameraBackend,
+    selection: CameraSelection,
+    file: &std::path::Path,
+) -> Result<()> {
+    debug!(?selection, "Exporting profile");
+
+    let cameras = Camera::enumerate(backend).context("Failed to enumerate devices")?;
+    let indices = parse_camera_selection(&selection, &cameras)?;
+
+    let profile = Profile::capture(indices.iter().map(|&idx| &cameras[idx]));
+    profile.save(file)?;
+
+    for (camera, &idx) in profile.cameras.iter().zip(&indices) {
+        println!(
+            "[{}] {}: {} properties",
+            idx,
+            camera.name,
+            camera.properties.len()
+        );
+    }
+    println!("Saved profile to {}", file.display());
     Ok(())
 }
 
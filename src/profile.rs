@@ -0,0 +1,453 @@
+/// Camera profiles
+///
+/// A profile is a snapshot of camera settings in a JSON file: for each camera, its name and
+/// USB IDs, and every supported property's value, Auto/Manual mode and capability range.
+/// Profiles are written by `export` and can be applied to other cameras to make them match.
+use crate::Camera;
+use crate::device_path::{self, DevicePath};
+use crate::webcam::{self, PropertyInfo, PropertyValue};
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use tracing::{debug, instrument};
+
+/// Profile format version written by this build
+pub const PROFILE_VERSION: u32 = 1;
+
+/// Contents of a profile file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    pub version: u32,
+    /// Tool and version that wrote the profile, e.g. "wincamcfg 0.3.2"
+    #[serde(default)]
+    pub generator: String,
+    pub cameras: Vec<CameraProfile>,
+}
+
+/// Settings of one camera, with enough metadata to find a matching camera later
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CameraProfile {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_path: Option<String>,
+    #[serde(
+        default,
+        serialize_with = "device_path::serialize_hex_id",
+        deserialize_with = "device_path::deserialize_hex_id",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub vendor_id: Option<u16>,
+    #[serde(
+        default,
+        serialize_with = "device_path::serialize_hex_id",
+        deserialize_with = "device_path::deserialize_hex_id",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub product_id: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub serial: Option<String>,
+    /// Property settings by canonical name, in enumeration order
+    pub properties: IndexMap<String, PropertySetting>,
+}
+
+/// A property's value and mode, with the range the camera reported for it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PropertySetting {
+    /// Current value, as a label for enum-like properties
+    pub value: PropertyValue,
+    /// Auto or Manual, for properties that support Auto mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<Mode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<i32>,
+}
+
+impl PropertySetting {
+    /// Value to write: Auto for properties saved in Auto mode, otherwise the saved value
+    pub fn target(&self) -> PropertyValue {
+        match self.mode {
+            Some(Mode::Auto) => PropertyValue::Auto,
+            _ => self.value.clone(),
+        }
+    }
+}
+
+/// Whether a property is driver-controlled or set to a fixed value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Mode {
+    Auto,
+    Manual,
+}
+
+impl Mode {
+    /// Current mode of a property, or None if it doesn't support Auto mode
+    pub fn of(property: &PropertyInfo) -> Option<Self> {
+        property
+            .caps
+            .filter(|caps| caps & webcam::FLAGS_AUTO != 0)
+            .and(property.current_flags)
+            .map(|flags| {
+                if flags & webcam::FLAGS_AUTO != 0 {
+                    Self::Auto
+                } else {
+                    Self::Manual
+                }
+            })
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Auto => "Auto",
+            Self::Manual => "Manual",
+        })
+    }
+}
+
+/// A property whose live state differs from what was expected
+#[derive(Debug, Clone, Serialize)]
+pub struct Difference {
+    pub property: String,
+    pub expected: PropertyValue,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected_mode: Option<Mode>,
+    pub actual: Option<PropertyValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual_mode: Option<Mode>,
+}
+
+impl Profile {
+    /// Snapshot the current settings of some cameras
+    pub fn capture<'a>(cameras: impl IntoIterator<Item = &'a Camera<'a>>) -> Self {
+        Self {
+            version: PROFILE_VERSION,
+            generator: format!("wincamcfg {}", env!("CARGO_PKG_VERSION")),
+            cameras: cameras.into_iter().map(CameraProfile::capture).collect(),
+        }
+    }
+
+    /// Read a profile file
+    #[instrument]
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read profile '{}'", path.display()))?;
+        let profile: Self = serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse profile '{}'", path.display()))?;
+        if profile.version > PROFILE_VERSION {
+            anyhow::bail!(
+                "Profile '{}' is version {}, but this build only understands version {}",
+                path.display(),
+                profile.version,
+                PROFILE_VERSION
+            );
+        }
+        debug!(camera_count = profile.cameras.len(), "Profile loaded");
+        Ok(profile)
+    }
+
+    /// The profile camera to apply to `camera`: the one `matching` finds, or else the only
+    /// camera of a single-camera profile, so one camera's settings can be copied to another
+    pub fn for_camera(&self, camera: &Camera) -> Option<&CameraProfile> {
+        self.matching(camera).or(match self.cameras.as_slice() {
+            [only] => Some(only),
+            _ => None,
+        })
+    }
+
+    /// The profile camera that is the same model or unit as `camera`. The best match wins:
+    /// same USB IDs and serial number, then same USB IDs, then same name.
+    pub fn matching(&self, camera: &Camera) -> Option<&CameraProfile> {
+        let parsed = camera
+            .device_path()
+            .map(DevicePath::parse)
+            .unwrap_or_default();
+        let same_ids = |p: &&CameraProfile| {
+            p.vendor_id.is_some()
+                && p.vendor_id == parsed.vendor_id
+                && p.product_id == parsed.product_id
+        };
+        let cameras = || self.cameras.iter();
+        cameras()
+            .filter(same_ids)
+            .find(|p| p.serial.is_some() && p.serial == parsed.serial)
+            .or_else(|| cameras().find(same_ids))
+            .or_else(|| cameras().find(|p| p.name.eq_ignore_ascii_case(camera.name())))
+    }
+
+    /// Write the profile to a file
+    #[instrument(skip(self))]
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize profile")?;
+        std::fs::write(path, json)
+            .with_context(|| format!("Failed to write profile '{}'", path.display()))
+    }
+}
+
+impl CameraProfile {
+    /// Expected state of a camera when every property is at its default. Modes are left
+    /// out, since cameras don't report a default mode.
+    pub fn defaults(camera: &Camera) -> Self {
+        let mut profile = Self::capture(camera);
+        profile.properties = camera
+            .properties()
+            .filter_map(|p| {
+                let setting = PropertySetting {
+                    value: PropertyValue::from_raw(&p.name, p.default?),
+                    mode: None,
+                    min: p.min,
+                    max: p.max,
+                    step: p.step,
+                    default: p.default,
+                };
+                Some((p.name.clone(), setting))
+            })
+            .collect();
+        profile
+    }
+
+    /// Compare a camera's properties, as of its last enumeration or read, against this
+    /// profile. Properties the camera doesn't support are ignored. A value is only
+    /// compared when the property is expected in, and is in, Manual mode (or doesn't
+    /// support Auto), since drivers choose the value in Auto mode.
+    pub fn compare(&self, camera: &Camera) -> Vec<Difference> {
+        self.properties
+            .iter()
+            .filter_map(|(name, setting)| {
+                let p = camera.property(name).ok()?;
+                let actual_mode = Mode::of(p);
+                let mode_differs = setting.mode.is_some() && setting.mode != actual_mode;
+                let compare_value =
+                    setting.mode != Some(Mode::Auto) && actual_mode != Some(Mode::Auto);
+                let value_differs = compare_value
+                    && match setting.value.raw(p) {
+                        Ok(Some(expected)) => p.current != Some(expected),
+                        // Auto without a mode, or a value this camera can't take
+                        Ok(None) | Err(_) => true,
+                    };
+                (mode_differs || value_differs).then(|| Difference {
+                    property: p.name.clone(),
+                    expected: setting.value.clone(),
+                    expected_mode: setting.mode,
+                    actual: p.current.map(|v| PropertyValue::from_raw(&p.name, v)),
+                    actual_mode,
+                })
+            })
+            .collect()
+    }
+
+    /// Properties in the order they should be written. PowerlineFrequency goes first, since
+    /// it limits the exposure times a camera will accept; then switches to Auto mode, so
+    /// the driver stops adjusting properties that are about to get manual values; then
+    /// manual values. Otherwise properties keep their profile order.
+    pub fn write_order(&self) -> Vec<(&str, &PropertySetting)> {
+        let mut order: Vec<(&str, &PropertySetting)> = self
+            .properties
+            .iter()
+            .map(|(name, setting)| (name.as_str(), setting))
+            .collect();
+        order.sort_by_key(|&(name, setting)| {
+            if name.eq_ignore_ascii_case("PowerlineFrequency") {
+                0
+            } else if setting.mode == Some(Mode::Auto) {
+                1
+            } else {
+                2
+            }
+        });
+        order
+    }
+
+    /// Snapshot a camera's properties as of its last enumeration or read. Properties
+    /// without a current value are left out.
+    pub fn capture(camera: &Camera) -> Self {
+        let parsed = camera
+            .device_path()
+            .map(DevicePath::parse)
+            .unwrap_or_default();
+        let properties = camera
+            .properties()
+            .filter_map(|p| {
+                let setting = PropertySetting {
+                    value: PropertyValue::from_raw(&p.name, p.current?),
+                    mode: Mode::of(p),
+                    min: p.min,
+                    max: p.max,
+                    step: p.step,
+                    default: p.default,
+                };
+                Some((p.name.clone(), setting))
+            })
+            .collect();
+        Self {
+            name: camera.name().to_string(),
+            device_path: camera.device_path().map(String::from),
+            vendor_id: parsed.vendor_id,
+            product_id: parsed.product_id,
+            serial: parsed.serial,
+            properties,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::simulated::SimulatedBackend;
+    use crate::test_support::{sample_backend, sample_devices};
+
+    // Write a profile out and read it back, the way export and apply see it
+    fn reload(profile: &Profile) -> Profile {
+        serde_json::from_str(&serde_json::to_string(profile).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn exported_labels_compare_equal_after_reload() {
+        let backend = sample_backend();
+        let mut camera = Camera::open(&backend, 0).unwrap();
+        // Every PowerlineFrequency label this camera accepts
+        for label in ["Disabled", "50Hz", "60Hz"] {
+            camera
+                .set("PowerlineFrequency", &PropertyValue::Label(label.into()))
+                .unwrap();
+            let profile = reload(&Profile::capture([&camera]));
+            let setting = &profile.cameras[0].properties["PowerlineFrequency"];
+            assert_eq!(setting.target(), PropertyValue::Label(label.into()));
+            assert!(profile.cameras[0].compare(&camera).is_empty(), "{}", label);
+        }
+    }
+
+    #[test]
+    fn label_auto_is_not_auto_mode_after_reload() {
+        let mut devices = sample_devices();
+        let powerline = devices[0]
+            .video_proc_amp_properties
+            .iter_mut()
+            .find(|p| p.name == "PowerlineFrequency")
+            .unwrap();
+        powerline.max = Some(3);
+        powerline.current = Some(3);
+        let backend = SimulatedBackend::new(devices);
+        let camera = Camera::open(&backend, 0).unwrap();
+
+        let profile = reload(&Profile::capture([&camera]));
+        let setting = &profile.cameras[0].properties["PowerlineFrequency"];
+        assert_eq!(setting.mode, None);
+        assert_eq!(setting.target(), PropertyValue::Label("Auto".into()));
+        assert!(profile.cameras[0].compare(&camera).is_empty());
+    }
+
+    fn setting(value: PropertyValue, mode: Option<Mode>) -> PropertySetting {
+        PropertySetting {
+            value,
+            mode,
+            min: None,
+            max: None,
+            step: None,
+            default: None,
+        }
+    }
+
+    #[test]
+    fn write_order_puts_powerline_then_auto_first() {
+        let manual = || setting(PropertyValue::Manual(1), Some(Mode::Manual));
+        let auto = || setting(PropertyValue::Manual(1), Some(Mode::Auto));
+        let profile = CameraProfile {
+            name: "Camera".to_string(),
+            device_path: None,
+            vendor_id: None,
+            product_id: None,
+            serial: None,
+            properties: [
+                ("Brightness", manual()),
+                ("Exposure", manual()),
+                ("WhiteBalance", auto()),
+                ("Contrast", setting(PropertyValue::Manual(1), None)),
+                ("Focus", auto()),
+                (
+                    "PowerlineFrequency",
+                    setting(PropertyValue::Label("50Hz".into()), None),
+                ),
+            ]
+            .into_iter()
+            .map(|(name, setting)| (name.to_string(), setting))
+            .collect(),
+        };
+        let order: Vec<&str> = profile
+            .write_order()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(
+            order,
+            [
+                "PowerlineFrequency",
+                "WhiteBalance",
+                "Focus",
+                "Brightness",
+                "Exposure",
+                "Contrast"
+            ]
+        );
+    }
+
+    #[test]
+    fn cameras_match_by_identity() {
+        let backend = sample_backend();
+        let cameras = Camera::enumerate(&backend).unwrap();
+        // Exported from the C920 only
+        let profile = Profile::capture([&cameras[0]]);
+
+        assert_eq!(
+            profile.matching(&cameras[0]).map(|p| p.name.as_str()),
+            Some("Logitech HD Pro Webcam C920")
+        );
+        for other in &cameras[1..] {
+            assert!(profile.matching(other).is_none(), "{}", other.name());
+            // apply still copies a single camera's settings anywhere
+            assert!(profile.for_camera(other).is_some());
+        }
+    }
+
+    #[test]
+    fn serial_beats_model_beats_name() {
+        let backend = sample_backend();
+        let cameras = Camera::enumerate(&backend).unwrap();
+        let kiyo = CameraProfile::capture(&cameras[3]);
+        let named = |name: &str, serial: Option<&str>, ids: bool| CameraProfile {
+            name: name.to_string(),
+            serial: serial.map(String::from),
+            vendor_id: kiyo.vendor_id.filter(|_| ids),
+            product_id: kiyo.product_id.filter(|_| ids),
+            ..kiyo.clone()
+        };
+        let profile = Profile {
+            version: PROFILE_VERSION,
+            generator: String::new(),
+            cameras: vec![
+                named("Razer Kiyo Pro", None, false),
+                named("by model", None, true),
+                named("by serial", Some("A1B2C3D4"), true),
+                named("other unit", Some("FFFF"), true),
+            ],
+        };
+        let name = |profile: &Profile| profile.matching(&cameras[3]).unwrap().name.clone();
+        assert_eq!(name(&profile), "by serial");
+
+        let mut without_serial = profile.clone();
+        without_serial.cameras.remove(2);
+        assert_eq!(name(&without_serial), "by model");
+
+        without_serial.cameras.remove(1);
+        without_serial.cameras.remove(1);
+        assert_eq!(name(&without_serial), "Razer Kiyo Pro");
+    }
+}
@@ -0,0 +1,459 @@
+/// Property registry
+///
+/// One table describing every DirectShow property the tool knows about: the interface and
+/// numeric ID it is addressed by, its canonical name and accepted aliases, a category, the
+/// unit its raw values are in, a one-line description, and the labels of enum-like values.
+/// Enumeration, name lookup and value formatting all read from this table, so adding a
+/// property means adding one entry here.
+use crate::webcam::PropertyType;
+use serde::Serialize;
+use std::fmt;
+
+/// Broad grouping of properties, used to organise descriptions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Category {
+    Image,
+    Color,
+    Exposure,
+    Lens,
+    Motion,
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Image => "Image",
+            Self::Color => "Color",
+            Self::Exposure => "Exposure",
+            Self::Lens => "Lens",
+            Self::Motion => "Motion",
+        })
+    }
+}
+
+/// Unit of a property's raw DirectShow value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Unit {
+    /// Driver-defined steps with no physical unit
+    None,
+    /// Color temperature in kelvin
+    Kelvin,
+    /// Exposure time as log2 seconds (-6 is 1/64s)
+    Log2Seconds,
+    /// Angle in degrees
+    Degrees,
+    /// Distance or focal length in millimeters
+    Millimeters,
+    /// Aperture as f-stop * 10
+    TenthFStop,
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::None => "none",
+            Self::Kelvin => "kelvin",
+            Self::Log2Seconds => "log2 seconds",
+            Self::Degrees => "degrees",
+            Self::Millimeters => "millimeters",
+            Self::TenthFStop => "f-stop x10",
+        })
+    }
+}
+
+impl Unit {
+    /// Format a raw value with its unit, e.g. `-6 (1/64s)` for Exposure or `10°` for Pan.
+    /// Returns None for unitless values.
+    pub fn format(self, raw: i32) -> Option<String> {
+        match self {
+            Self::None => None,
+            Self::Kelvin => Some(format!("{}K", raw)),
+            // In f64, since i32::MIN can't be negated
+            Self::Log2Seconds if raw < 0 => {
+                Some(format!("{} (1/{}s)", raw, 2f64.powf(-f64::from(raw))))
+            }
+            Self::Log2Seconds => Some(format!("{} ({}s)", raw, 2f64.powi(raw))),
+            Self::Degrees => Some(format!("{}°", raw)),
+            Self::Millimeters => Some(format!("{}mm", raw)),
+            Self::TenthFStop => Some(format!("{} (f/{})", raw, f64::from(raw) / 10.0)),
+        }
+    }
+
+    /// Parse a value written with this unit (`1/60s`, `15deg`, `50mm`, `f/2.8`, `5000K`)
+    /// and convert it to the nearest raw value. Returns None if the text is not a
+    /// value in this unit.
+    pub fn parse(self, text: &str) -> Option<i32> {
+        let text = text.trim().to_ascii_lowercase();
+        let raw = match self {
+            Self::None => return None,
+            Self::Kelvin => parse_number(text.strip_suffix('k')?)?,
+            Self::Log2Seconds => {
+                let seconds = match text.strip_suffix("ms") {
+                    Some(ms) => parse_number(ms)? / 1000.0,
+                    None => parse_number(text.strip_suffix('s')?)?,
+                };
+                if seconds <= 0.0 {
+                    return None;
+                }
+                seconds.log2()
+            }
+            Self::Degrees => parse_number(
+                text.strip_suffix('°')
+                    .or_else(|| text.strip_suffix("deg"))?,
+            )?,
+            Self::Millimeters => parse_number(text.strip_suffix("mm")?)?,
+            Self::TenthFStop => parse_number(text.strip_prefix("f/")?)? * 10.0,
+        };
+        let raw = raw.round();
+        (raw >= f64::from(i32::MIN) && raw <= f64::from(i32::MAX)).then_some(raw as i32)
+    }
+
+    /// Example of a value in this unit, for error messages
+    pub fn example(self) -> Option<&'static str> {
+        match self {
+            Self::None => None,
+            Self::Kelvin => Some("5000K"),
+            Self::Log2Seconds => Some("1/60s"),
+            Self::Degrees => Some("15deg"),
+            Self::Millimeters => Some("50mm"),
+            Self::TenthFStop => Some("f/2.8"),
+        }
+    }
+}
+
+// Parse a decimal number or a fraction like "1/60"
+fn parse_number(text: &str) -> Option<f64> {
+    let text = text.trim();
+    let value = match text.split_once('/') {
+        Some((num, den)) => num.trim().parse::<f64>().ok()? / den.trim().parse::<f64>().ok()?,
+        None => text.parse::<f64>().ok()?,
+    };
+    value.is_finite().then_some(value)
+}
+
+/// Static description of a single property
+#[derive(Debug)]
+pub struct PropertyDef {
+    pub property_type: PropertyType,
+    /// Numeric property ID from ksmedia.h
+    pub id: i32,
+    /// Canonical name, used in output and fixtures
+    pub name: &'static str,
+    /// Alternative names accepted on input (case-insensitive)
+    pub aliases: &'static [&'static str],
+    pub category: Category,
+    pub unit: Unit,
+    pub description: &'static str,
+    /// Labels for enum-like values as (value, label); empty for numeric properties
+    pub labels: &'static [(i32, &'static str)],
+}
+
+impl PropertyDef {
+    /// Whether `name` is this property's canonical name or one of its aliases
+    pub fn matches(&self, name: &str) -> bool {
+        self.name.eq_ignore_ascii_case(name)
+            || self.aliases.iter().any(|a| a.eq_ignore_ascii_case(name))
+    }
+
+    /// Label for a raw value, if the property has one for it
+    pub fn label(&self, value: i32) -> Option<&'static str> {
+        self.labels
+            .iter()
+            .find(|&&(v, _)| v == value)
+            .map(|&(_, label)| label)
+    }
+}
+
+const ON_OFF: &[(i32, &str)] = &[(0, "Off"), (1, "On")];
+
+const fn def(
+    property_type: PropertyType,
+    id: i32,
+    name: &'static str,
+    category: Category,
+    unit: Unit,
+    description: &'static str,
+) -> PropertyDef {
+    PropertyDef {
+        property_type,
+        id,
+        name,
+        aliases: &[],
+        category,
+        unit,
+        description,
+        labels: &[],
+    }
+}
+
+const fn video_proc_amp(
+    id: i32,
+    name: &'static str,
+    category: Category,
+    description: &'static str,
+) -> PropertyDef {
+    def(
+        PropertyType::VideoProcAmp,
+        id,
+        name,
+        category,
+        Unit::None,
+        description,
+    )
+}
+
+const fn camera_control(
+    id: i32,
+    name: &'static str,
+    category: Category,
+    unit: Unit,
+    description: &'static str,
+) -> PropertyDef {
+    def(
+        PropertyType::CameraControl,
+        id,
+        name,
+        category,
+        unit,
+        description,
+    )
+}
+
+/// Every known property, VideoProcAmp first, in the order they are enumerated and shown
+pub const PROPERTIES: &[PropertyDef] = &[
+    video_proc_amp(0, "Brightness", Category::Image, "Black level of the image"),
+    video_proc_amp(
+        1,
+        "Contrast",
+        Category::Image,
+        "Difference between light and dark areas",
+    ),
+    video_proc_amp(3, "Saturation", Category::Color, "Intensity of colors"),
+    video_proc_amp(2, "Hue", Category::Color, "Color tint, rotating all hues"),
+    PropertyDef {
+        aliases: &["WB", "WhiteBalanceTemperature"],
+        unit: Unit::Kelvin,
+        ..video_proc_amp(
+            7,
+            "WhiteBalance",
+            Category::Color,
+            "Color temperature the image is balanced for",
+        )
+    },
+    video_proc_amp(
+        12,
+        "WhiteBalanceComponent",
+        Category::Color,
+        "White balance as separate blue and red components",
+    ),
+    PropertyDef {
+        aliases: &["ColourEnable"],
+        labels: ON_OFF,
+        ..video_proc_amp(
+            6,
+            "ColorEnable",
+            Category::Color,
+            "Color output; Off gives a monochrome image",
+        )
+    },
+    video_proc_amp(5, "Gamma", Category::Image, "Gamma correction curve"),
+    video_proc_amp(
+        4,
+        "Sharpness",
+        Category::Image,
+        "Strength of edge enhancement",
+    ),
+    PropertyDef {
+        aliases: &["Backlight", "BLC"],
+        labels: ON_OFF,
+        ..video_proc_amp(
+            8,
+            "BacklightCompensation",
+            Category::Image,
+            "Brightens subjects in front of a bright background",
+        )
+    },
+    video_proc_amp(9, "Gain", Category::Exposure, "Signal amplification"),
+    PropertyDef {
+        aliases: &["AntiFlicker", "Flicker"],
+        labels: &[(0, "Disabled"), (1, "50Hz"), (2, "60Hz"), (3, "Auto")],
+        ..video_proc_amp(
+            13,
+            "PowerlineFrequency",
+            Category::Exposure,
+            "Anti-flicker setting matching the mains frequency of room lighting",
+        )
+    },
+    PropertyDef {
+        aliases: &["DigitalZoom"],
+        ..video_proc_amp(
+            10,
+            "DigitalMultiplier",
+            Category::Lens,
+            "Digital zoom multiplier",
+        )
+    },
+    video_proc_amp(
+        11,
+        "DigitalMultiplierLimit",
+        Category::Lens,
+        "Upper limit for the digital zoom multiplier",
+    ),
+    camera_control(
+        4,
+        "Exposure",
+        Category::Exposure,
+        Unit::Log2Seconds,
+        "Exposure time",
+    ),
+    camera_control(
+        6,
+        "Focus",
+        Category::Lens,
+        Unit::Millimeters,
+        "Distance to the optimally focused target",
+    ),
+    camera_control(
+        0,
+        "Pan",
+        Category::Motion,
+        Unit::Degrees,
+        "Horizontal camera angle",
+    ),
+    camera_control(
+        1,
+        "Tilt",
+        Category::Motion,
+        Unit::Degrees,
+        "Vertical camera angle",
+    ),
+    camera_control(
+        2,
+        "Roll",
+        Category::Motion,
+        Unit::Degrees,
+        "Rotation around the lens axis",
+    ),
+    camera_control(
+        3,
+        "Zoom",
+        Category::Lens,
+        Unit::Millimeters,
+        "Optical zoom as lens focal length",
+    ),
+    PropertyDef {
+        aliases: &["Aperture"],
+        ..camera_control(
+            5,
+            "Iris",
+            Category::Exposure,
+            Unit::TenthFStop,
+            "Lens aperture",
+        )
+    },
+];
+
+/// Properties of one interface, in enumeration order
+pub fn of_type(property_type: PropertyType) -> impl Iterator<Item = &'static PropertyDef> {
+    PROPERTIES
+        .iter()
+        .filter(move |p| p.property_type == property_type)
+}
+
+/// Look up a property by canonical name or alias (case-insensitive)
+pub fn lookup(name: &str) -> Option<&'static PropertyDef> {
+    PROPERTIES.iter().find(|p| p.matches(name))
+}
+
+/// Look up a property by interface and numeric ID
+pub fn by_id(property_type: PropertyType, id: i32) -> Option<&'static PropertyDef> {
+    PROPERTIES
+        .iter()
+        .find(|p| p.property_type == property_type && p.id == id)
+}
+
+/// Look up a property of one interface by canonical name (case-insensitive)
+pub fn by_name(property_type: PropertyType, name: &str) -> Option<&'static PropertyDef> {
+    of_type(property_type).find(|p| p.name.eq_ignore_ascii_case(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_values_with_units() {
+        assert_eq!(Unit::None.format(5), None);
+        assert_eq!(Unit::Kelvin.format(4000).as_deref(), Some("4000K"));
+        assert_eq!(Unit::Log2Seconds.format(-6).as_deref(), Some("-6 (1/64s)"));
+        assert_eq!(Unit::Log2Seconds.format(0).as_deref(), Some("0 (1s)"));
+        assert_eq!(Unit::Log2Seconds.format(1).as_deref(), Some("1 (2s)"));
+        // Drivers and fixtures can report anything
+        assert_eq!(
+            Unit::Log2Seconds.format(i32::MIN).as_deref(),
+            Some("-2147483648 (1/infs)")
+        );
+        assert_eq!(
+            Unit::Log2Seconds.format(i32::MAX).as_deref(),
+            Some("2147483647 (infs)")
+        );
+        assert_eq!(Unit::Degrees.format(-10).as_deref(), Some("-10°"));
+        assert_eq!(Unit::Millimeters.format(50).as_deref(), Some("50mm"));
+        assert_eq!(Unit::TenthFStop.format(28).as_deref(), Some("28 (f/2.8)"));
+    }
+
+    #[test]
+    fn parse_exposure_times() {
+        let unit = Unit::Log2Seconds;
+        assert_eq!(unit.parse("1/64s"), Some(-6));
+        // 1/60s is log2 -5.9, the nearest step is 1/64s
+        assert_eq!(unit.parse("1/60s"), Some(-6));
+        assert_eq!(unit.parse("1/30S"), Some(-5));
+        assert_eq!(unit.parse("1s"), Some(0));
+        assert_eq!(unit.parse("2s"), Some(1));
+        assert_eq!(unit.parse("15.6ms"), Some(-6));
+        assert_eq!(unit.parse("0s"), None);
+        assert_eq!(unit.parse("-1s"), None);
+        assert_eq!(unit.parse("1/0s"), None);
+        assert_eq!(unit.parse("-6"), None);
+    }
+
+    #[test]
+    fn parse_other_units() {
+        assert_eq!(Unit::None.parse("5"), None);
+        assert_eq!(Unit::Kelvin.parse("5000K"), Some(5000));
+        assert_eq!(Unit::Kelvin.parse("5000"), None);
+        assert_eq!(Unit::Degrees.parse("15deg"), Some(15));
+        assert_eq!(Unit::Degrees.parse("-7.5°"), Some(-8));
+        assert_eq!(Unit::Millimeters.parse("50 mm"), Some(50));
+        assert_eq!(Unit::TenthFStop.parse("f/2.8"), Some(28));
+        assert_eq!(Unit::TenthFStop.parse("2.8"), None);
+        assert_eq!(Unit::Kelvin.parse("1e12K"), None);
+    }
+
+    #[test]
+    fn formatted_values_parse_back() {
+        for unit in [Unit::Kelvin, Unit::Degrees, Unit::Millimeters] {
+            for raw in [-90, 0, 15, 6500] {
+                assert_eq!(
+                    unit.parse(&unit.format(raw).unwrap()),
+                    Some(raw),
+                    "{}",
+                    unit
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn lookup_by_name_alias_and_id() {
+        assert_eq!(lookup("antiflicker").unwrap().name, "PowerlineFrequency");
+        assert_eq!(lookup("WB").unwrap().name, "WhiteBalance");
+        assert_eq!(
+            by_id(PropertyType::CameraControl, 4).unwrap().name,
+            "Exposure"
+        );
+        assert!(by_name(PropertyType::VideoProcAmp, "Exposure").is_none());
+        assert!(lookup("Nonsense").is_none());
+    }
+}
@@ -0,0 +1,468 @@
+/// Camera selectors
+///
+/// A selector picks cameras by something more stable than their enumeration index, which
+/// changes whenever a USB camera is replugged:
+///
+/// - `0`: enumeration index
+/// - `1-3`: range of enumeration indices (inclusive)
+/// - `all`: every camera
+/// - `name:Logitech*`: friendly name, as a case-insensitive glob (`*` and `?`)
+/// - `regex:^Logitech .*C920$`: friendly name, as a regular expression
+/// - `path:\\?\usb#vid_046d&...`: exact device path (case-insensitive)
+/// - `vidpid:046d:082d`: USB vendor and product ID, in hex
+/// - `serial:ABC123`: USB serial number (case-insensitive)
+/// - `@podium`: a camera alias saved with `wincamcfg alias add`
+///
+/// A selection combines selectors: `0,2,3` or `all,!2`. Terms are comma-separated and a
+/// leading `!` excludes cameras. Because commas can appear in regular expressions and device
+/// paths, a `regex:` or `path:` selector takes the rest of its argument.
+use crate::alias::{Aliases, CameraAlias};
+use crate::device_path::DevicePath;
+use crate::webcam::DeviceInfo;
+use anyhow::{Context, Result};
+use regex_lite::Regex;
+use std::fmt;
+use std::str::FromStr;
+
+/// A way of picking cameras out of the enumerated devices
+#[derive(Debug, Clone)]
+pub enum Selector {
+    All,
+    Index(usize),
+    Range { start: usize, end: usize },
+    Name(String),
+    Regex(Regex),
+    Path(String),
+    VidPid { vendor_id: u16, product_id: u16 },
+    Serial(String),
+    Alias { name: String, alias: CameraAlias },
+}
+
+impl Selector {
+    // Whether the camera at `index` with the given device information is selected, looking
+    // at that camera alone. Aliases need every camera to resolve; see `matching`.
+    fn matches(&self, index: usize, device: &DeviceInfo) -> bool {
+        let name = device.name.as_deref().unwrap_or("Unknown");
+        let path = device.device_path.as_deref();
+        match self {
+            Self::All => true,
+            Self::Index(i) => *i == index,
+            Self::Range { start, end } => (*start..=*end).contains(&index),
+            Self::Name(pattern) => glob_match(pattern, name),
+            Self::Regex(regex) => regex.is_match(name),
+            Self::Path(p) => path.is_some_and(|path| path.eq_ignore_ascii_case(p)),
+            Self::VidPid {
+                vendor_id,
+                product_id,
+            } => path.map(DevicePath::parse).is_some_and(|parsed| {
+                parsed.vendor_id == Some(*vendor_id) && parsed.product_id == Some(*product_id)
+            }),
+            Self::Serial(serial) => path
+                .map(DevicePath::parse)
+                .and_then(|parsed| parsed.serial)
+                .is_some_and(|s| s.eq_ignore_ascii_case(serial)),
+            Self::Alias { alias, .. } => !alias.resolve([path]).is_empty(),
+        }
+    }
+
+    /// Indices of the selected cameras, in enumeration order. Fails when an index is out
+    /// of range or nothing matches.
+    pub fn select<'a>(
+        &self,
+        devices: impl IntoIterator<Item = &'a DeviceInfo>,
+    ) -> Result<Vec<usize>> {
+        let devices: Vec<&DeviceInfo> = devices.into_iter().collect();
+        let last = match self {
+            Self::Index(index) => Some(*index),
+            Self::Range { end, .. } => Some(*end),
+            _ => None,
+        };
+        if let Some(index) = last
+            && index >= devices.len()
+        {
+            anyhow::bail!(
+                "Camera index {} not found (only {} devices available)",
+                index,
+                devices.len()
+            );
+        }
+
+        let selected = self.matching(devices);
+        if selected.is_empty() && !matches!(self, Self::All) {
+            anyhow::bail!("No camera matches '{}'", self);
+        }
+        Ok(selected)
+    }
+
+    /// Indices of the cameras the selector picks, in enumeration order. Unlike `select`,
+    /// matching nothing is not an error, so this is what exclusions use.
+    pub fn matching<'a>(&self, devices: impl IntoIterator<Item = &'a DeviceInfo>) -> Vec<usize> {
+        let devices: Vec<&DeviceInfo> = devices.into_iter().collect();
+        match self {
+            // Prefer the recorded path over the hardware IDs
+            Self::Alias { alias, .. } => {
+                alias.resolve(devices.iter().map(|d| d.device_path.as_deref()))
+            }
+            _ => devices
+                .iter()
+                .enumerate()
+                .filter(|&(index, device)| self.matches(index, device))
+                .map(|(index, _)| index)
+                .collect(),
+        }
+    }
+
+    /// Parse a selector, looking up `@alias` selectors in `aliases`
+    pub fn parse(selector: &str, aliases: &Aliases) -> Result<Self> {
+        // Sanitize input: device paths are long, but nothing legitimate is this long
+        if selector.len() > 512 {
+            anyhow::bail!("Camera selection string exceeds maximum allowed length");
+        }
+        if selector.chars().any(char::is_control) {
+            anyhow::bail!("Camera selection contains control characters");
+        }
+
+        if selector.eq_ignore_ascii_case("all") {
+            return Ok(Self::All);
+        }
+        if let Some(name) = selector.strip_prefix('@') {
+            let alias = aliases
+                .get(name)
+                .with_context(|| format!("Unknown camera alias '@{}'", name))?;
+            return Ok(Self::Alias {
+                name: name.to_string(),
+                alias: alias.clone(),
+            });
+        }
+        if !selector.is_empty() && selector.chars().all(|c| c.is_ascii_digit()) {
+            let index = selector
+                .parse()
+                .with_context(|| format!("Invalid camera index: {}", selector))?;
+            return Ok(Self::Index(index));
+        }
+        if let Some((start, end)) = selector.split_once('-')
+            && !start.is_empty()
+            && !end.is_empty()
+            && start.chars().chain(end.chars()).all(|c| c.is_ascii_digit())
+        {
+            let parse = |index: &str| {
+                index
+                    .parse::<usize>()
+                    .with_context(|| format!("Invalid camera index: {}", index))
+            };
+            let (start, end) = (parse(start)?, parse(end)?);
+            if start > end {
+                anyhow::bail!("Invalid camera range '{}': start is after end", selector);
+            }
+            return Ok(Self::Range { start, end });
+        }
+
+        let Some((kind, value)) = selector.split_once(':') else {
+            anyhow::bail!(
+                "Invalid camera selection '{}': expected an index, a range like 1-3, 'all', an @alias, or one of name:, regex:, path:, vidpid: or serial:",
+                selector
+            );
+        };
+        // Quotes survive some shells (e.g. cmd.exe) when the whole argument is quoted
+        let value = value.trim_matches('"');
+        if value.is_empty() {
+            anyhow::bail!("Camera selection '{}' has an empty value", selector);
+        }
+
+        match kind.to_ascii_lowercase().as_str() {
+            "name" => Ok(Self::Name(value.to_string())),
+            "regex" => Regex::new(value)
+                .map(Self::Regex)
+                .with_context(|| format!("Invalid regular expression '{}'", value)),
+            "path" => Ok(Self::Path(value.to_string())),
+            "vidpid" => {
+                let (vid, pid) = value
+                    .split_once(':')
+                    .with_context(|| format!("Invalid vidpid '{}': expected VID:PID", value))?;
+                let parse = |id: &str| {
+                    u16::from_str_radix(id.trim_start_matches("0x"), 16)
+                        .with_context(|| format!("Invalid USB ID '{}': expected 4 hex digits", id))
+                };
+                Ok(Self::VidPid {
+                    vendor_id: parse(vid)?,
+                    product_id: parse(pid)?,
+                })
+            }
+            "serial" => Ok(Self::Serial(value.to_string())),
+            _ => anyhow::bail!(
+                "Unknown camera selector '{}:': expected name:, regex:, path:, vidpid: or serial:",
+                kind
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::All => f.write_str("all"),
+            Self::Index(index) => write!(f, "{}", index),
+            Self::Range { start, end } => write!(f, "{}-{}", start, end),
+            Self::Name(pattern) => write!(f, "name:{}", pattern),
+            Self::Regex(regex) => write!(f, "regex:{}", regex.as_str()),
+            Self::Path(path) => write!(f, "path:{}", path),
+            Self::VidPid {
+                vendor_id,
+                product_id,
+            } => write!(f, "vidpid:{:04x}:{:04x}", vendor_id, product_id),
+            Self::Serial(serial) => write!(f, "serial:{}", serial),
+            Self::Alias { name, .. } => write!(f, "@{}", name),
+        }
+    }
+}
+
+impl FromStr for Selector {
+    type Err = anyhow::Error;
+
+    /// Parse a selector. `@alias` selectors need `Selector::parse` to look up the alias.
+    fn from_str(selector: &str) -> Result<Self> {
+        Self::parse(selector, &Aliases::default())
+    }
+}
+
+// Case-insensitive glob match supporting `*` (any run of characters) and `?` (one character)
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // Position after the last '*' and the text position it was tried against
+    let mut backtrack = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p + 1, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                // Let the last '*' swallow one more character and retry
+                Some((star_p, star_t)) => {
+                    p = star_p;
+                    t = star_t + 1;
+                    backtrack = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// One term of a camera selection
+#[derive(Debug, Clone)]
+pub enum Term {
+    Include(Selector),
+    Exclude(Selector),
+}
+
+/// Parse camera selection arguments (e.g. from repeated `--camera` flags) into terms, in
+/// order, looking up `@alias` selectors in `aliases`
+pub fn parse_selection<S: AsRef<str>>(args: &[S], aliases: &Aliases) -> Result<Vec<Term>> {
+    let mut terms = Vec::new();
+    for arg in args {
+        let mut rest = arg.as_ref();
+        while !rest.is_empty() {
+            let (negated, body) = match rest.strip_prefix('!') {
+                Some(body) => (true, body),
+                None => (false, rest),
+            };
+            // regex: and path: values may contain commas, so they run to the end
+            let takes_rest = ["regex:", "path:"].iter().any(|kind| {
+                body.get(..kind.len())
+                    .is_some_and(|prefix| prefix.eq_ignore_ascii_case(kind))
+            });
+            let (term, remainder) = match body.split_once(',') {
+                Some((term, remainder)) if !takes_rest => (term, remainder),
+                _ => (body, ""),
+            };
+
+            let selector = Selector::parse(term.trim(), aliases)?;
+            terms.push(if negated {
+                Term::Exclude(selector)
+            } else {
+                Term::Include(selector)
+            });
+            rest = remainder;
+        }
+    }
+    if terms.is_empty() {
+        anyhow::bail!("Camera selection is empty");
+    }
+    Ok(terms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn devices() -> Vec<DeviceInfo> {
+        vec![
+            DeviceInfo::named("Logitech HD Pro Webcam C920").with_path(r"\\?\usb#vid_046d&pid_082d&mi_00#7&1c3f2a4b&0&0000#{65e8773d-8f56-11d0-a3b9-00a0c9223196}\global"),
+            DeviceInfo::named("OBS Virtual Camera").with_path(r"\\?\root#image#0000#{65e8773d-8f56-11d0-a3b9-00a0c9223196}\global"),
+            DeviceInfo::named("Logitech BRIO").with_path(r"\\?\usb#vid_046d&pid_085e&mi_00#7&2a8e9e3f&0&0000#{65e8773d-8f56-11d0-a3b9-00a0c9223196}\global"),
+            DeviceInfo::named("Razer Kiyo Pro").with_path(r"\\?\usb#vid_1532&pid_0e05#A1B2C3D4#{65e8773d-8f56-11d0-a3b9-00a0c9223196}\global"),
+        ]
+    }
+
+    fn select(selector: &str) -> Result<Vec<usize>> {
+        selector.parse::<Selector>()?.select(&devices())
+    }
+
+    #[test]
+    fn glob_wildcards() {
+        assert!(glob_match("Logitech*", "Logitech BRIO"));
+        assert!(glob_match("*brio", "Logitech BRIO"));
+        assert!(glob_match("*HD*C9?0", "Logitech HD Pro Webcam C920"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a*b*c", "aXXbYYbZZc"));
+        assert!(!glob_match("Logitech", "Logitech BRIO"));
+        assert!(!glob_match("?", ""));
+        assert!(!glob_match("*C930", "Logitech HD Pro Webcam C920"));
+    }
+
+    #[test]
+    fn glob_is_case_insensitive() {
+        assert!(glob_match("LOGITECH brio", "Logitech BRIO"));
+    }
+
+    #[test]
+    fn select_by_name_and_regex() {
+        assert_eq!(select("name:Logitech*").unwrap(), vec![0, 2]);
+        assert_eq!(select("name:razer kiyo pro").unwrap(), vec![3]);
+        assert_eq!(select("regex:^Logitech .*C920$").unwrap(), vec![0]);
+        assert!(select("name:Nothing*").is_err());
+        assert!(select("regex:(").is_err());
+    }
+
+    #[test]
+    fn select_by_hardware() {
+        assert_eq!(select("vidpid:046d:085e").unwrap(), vec![2]);
+        assert_eq!(select("vidpid:0x046D:0x082D").unwrap(), vec![0]);
+        assert_eq!(select("serial:a1b2c3d4").unwrap(), vec![3]);
+        let path = devices()[2].device_path.clone().unwrap();
+        assert_eq!(
+            select(&format!("path:{}", path.to_uppercase())).unwrap(),
+            vec![2]
+        );
+        assert!(select("vidpid:046d").is_err());
+        assert!(select("vidpid:zzzz:0000").is_err());
+        assert!(select("serial:FFFF").is_err());
+    }
+
+    #[test]
+    fn select_by_index() {
+        assert_eq!(select("3").unwrap(), vec![3]);
+        assert_eq!(select("ALL").unwrap(), vec![0, 1, 2, 3]);
+        assert!(select("4").is_err());
+    }
+
+    #[test]
+    fn invalid_selectors() {
+        assert!("".parse::<Selector>().is_err());
+        assert!("bogus".parse::<Selector>().is_err());
+        assert!("color:red".parse::<Selector>().is_err());
+        assert!("name:".parse::<Selector>().is_err());
+        assert!("name:a\tb".parse::<Selector>().is_err());
+        assert!("@room".parse::<Selector>().is_err());
+    }
+
+    #[test]
+    fn quoted_values_are_unquoted() {
+        assert_eq!(select(r#"name:"Logitech BRIO""#).unwrap(), vec![2]);
+    }
+
+    #[test]
+    fn selectors_display_as_parsed() {
+        for selector in ["all", "2", "name:Logi*", "vidpid:046d:082d", "serial:A1B2"] {
+            assert_eq!(selector.parse::<Selector>().unwrap().to_string(), selector);
+        }
+    }
+
+    // Terms of a selection as strings, e.g. "+0" and "-2"
+    fn terms(args: &[&str]) -> Result<Vec<String>> {
+        Ok(parse_selection(args, &Aliases::default())?
+            .into_iter()
+            .map(|term| match term {
+                Term::Include(selector) => format!("+{}", selector),
+                Term::Exclude(selector) => format!("-{}", selector),
+            })
+            .collect())
+    }
+
+    #[test]
+    fn parse_lists_and_exclusions() {
+        assert_eq!(terms(&["0,2,3"]).unwrap(), ["+0", "+2", "+3"]);
+        assert_eq!(terms(&["all,!2"]).unwrap(), ["+all", "-2"]);
+        assert_eq!(terms(&["!name:OBS*"]).unwrap(), ["-name:OBS*"]);
+        assert_eq!(terms(&[" 0 , 1 "]).unwrap(), ["+0", "+1"]);
+    }
+
+    #[test]
+    fn parse_ranges() {
+        assert_eq!(terms(&["1-3"]).unwrap(), ["+1-3"]);
+        assert_eq!(terms(&["2-2,!1-1"]).unwrap(), ["+2-2", "-1-1"]);
+        assert!(terms(&["3-1"]).is_err());
+        assert!(terms(&["1-"]).is_err());
+        assert!(terms(&["-1"]).is_err());
+        assert!(select("2-5").is_err());
+        assert_eq!(select("1-3").unwrap(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn repeated_arguments_are_concatenated() {
+        assert_eq!(
+            terms(&["0", "vidpid:046d:085e", "!3"]).unwrap(),
+            ["+0", "+vidpid:046d:085e", "-3"]
+        );
+    }
+
+    #[test]
+    fn regex_and_path_take_the_rest_of_the_argument() {
+        assert_eq!(
+            terms(&["0,regex:^a{1,2}$"]).unwrap(),
+            ["+0", "+regex:^a{1,2}$"]
+        );
+        assert_eq!(terms(&["PATH:a,b", "1"]).unwrap(), ["+path:a,b", "+1"]);
+    }
+
+    #[test]
+    fn empty_selections_are_rejected() {
+        assert!(terms(&[]).is_err());
+        assert!(terms(&[""]).is_err());
+        assert!(terms(&["0,,1"]).is_err());
+    }
+
+    #[test]
+    fn alias_prefers_its_recorded_path() {
+        let devices = devices();
+        // Recorded on the C920's path, with the Kiyo's IDs and serial number
+        let alias = CameraAlias {
+            device_path: devices[0].device_path.clone(),
+            ..CameraAlias::new(&devices[3])
+        };
+        let selector = Selector::Alias {
+            name: "podium".to_string(),
+            alias: alias.clone(),
+        };
+        assert_eq!(selector.matching(&devices), vec![0]);
+
+        // The serial number is the fallback once the path is gone
+        let moved = Selector::Alias {
+            name: "podium".to_string(),
+            alias: CameraAlias {
+                device_path: Some("gone".to_string()),
+                ..alias
+            },
+        };
+        assert_eq!(moved.matching(&devices), vec![3]);
+    }
+}
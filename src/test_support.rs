@@ -0,0 +1,57 @@
+//! Shared test fixtures: the sample cameras, and builders for hand-made devices and properties
+use crate::backend::simulated::SimulatedBackend;
+use crate::registry;
+use crate::webcam::{DeviceInfo, FLAGS_MANUAL, PropertyInfo, PropertyType};
+
+/// Cameras in `fixtures/sample-cameras.json`
+pub fn sample_devices() -> Vec<DeviceInfo> {
+    serde_json::from_str(include_str!("../fixtures/sample-cameras.json")).unwrap()
+}
+
+/// Simulated backend serving the sample cameras
+pub fn sample_backend() -> SimulatedBackend {
+    SimulatedBackend::new(sample_devices())
+}
+
+impl DeviceInfo {
+    /// A device with a name and no path, location or properties
+    pub fn named(name: &str) -> Self {
+        Self {
+            name: Some(name.to_string()),
+            device_path: None,
+            location: None,
+            video_proc_amp_properties: Vec::new(),
+            camera_control_properties: Vec::new(),
+        }
+    }
+
+    pub fn with_path(mut self, path: &str) -> Self {
+        self.device_path = Some(path.to_string());
+        self
+    }
+
+    pub fn with_location(mut self, location: &str) -> Self {
+        self.location = Some(location.to_string());
+        self
+    }
+}
+
+impl PropertyInfo {
+    /// A Manual-only property with the given range, set to its minimum. The interface is
+    /// taken from the registry.
+    pub fn ranged(name: &str, min: i32, max: i32, step: i32) -> Self {
+        Self {
+            name: name.to_string(),
+            min: Some(min),
+            max: Some(max),
+            step: Some(step),
+            default: Some(min),
+            caps: Some(FLAGS_MANUAL),
+            current: Some(min),
+            current_flags: Some(FLAGS_MANUAL),
+            capabilities: None,
+            property_type: registry::lookup(name)
+                .map_or(PropertyType::VideoProcAmp, |p| p.property_type),
+        }
+    }
+}
@@ -1,183 +1,22 @@
-/// Webcam and DirectShow interaction module
+/// Webcam domain module
 ///
-/// This module handles all DirectShow COM interactions for webcam device enumeration,
-/// property querying and setting, and device information retrieval. It provides a domain
-/// layer abstraction over Windows DirectShow APIs with type-safe property enums and
-/// value formatting.
+/// This module holds the domain layer for webcam device enumeration, property querying
+/// and setting, and device information retrieval. It provides value parsing and formatting,
+/// driven by the property registry, on top of a `CameraBackend`, which performs the actual
+/// device access.
+use crate::backend::CameraBackend;
+use crate::device_path::{Bus, DevicePath};
+use crate::registry::{self, PropertyDef};
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::str::FromStr;
 use tracing::{debug, instrument, trace};
-use windows::{
-    Win32::Foundation::*, Win32::Media::DirectShow::*,
-    Win32::System::Com::StructuredStorage::IPropertyBag, Win32::System::Com::*, core::*,
-};
 
-/// RAII guard for COM initialization/cleanup
-///
-/// COM interfaces (ICreateDevEnum, IEnumMoniker, IMoniker, IPropertyBag, etc.)
-/// are automatically cleaned up when they go out of scope via their Drop implementations
-/// in the windows-rs crate. This guard only handles CoInitialize/CoUninitialize.
-struct ComGuard;
-
-impl ComGuard {
-    unsafe fn new() -> Result<Self> {
-        unsafe {
-            let hr = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
-            // S_OK (0) = initialized, S_FALSE (1) = already initialized
-            // Both are considered success for our purposes
-            if hr.is_err() {
-                return Err(anyhow::anyhow!("Failed to initialize COM: {:?}", hr));
-            }
-        }
-        Ok(ComGuard)
-    }
-}
-
-impl Drop for ComGuard {
-    fn drop(&mut self) {
-        unsafe {
-            CoUninitialize();
-        }
-    }
-}
-
-// DirectShow GUIDs for device enumeration
-const CLSID_SYSTEM_DEVICE_ENUM: GUID = GUID::from_u128(0x62be5d10_60eb_11d0_bd3b_00a0c911ce86);
-const CLSID_VIDEO_INPUT_DEVICE_CATEGORY: GUID =
-    GUID::from_u128(0x860bb310_5d01_11d0_bd3b_00a0c911ce86);
-
-/// VideoProcAmp property IDs from DirectShow (ksmedia.h)
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-#[repr(i32)]
-pub enum VideoProcAmpProperty {
-    Brightness = 0,
-    Contrast = 1,
-    Hue = 2,
-    Saturation = 3,
-    Sharpness = 4,
-    Gamma = 5,
-    ColorEnable = 6,
-    WhiteBalance = 7,
-    BacklightCompensation = 8,
-    Gain = 9,
-    DigitalMultiplier = 10,
-    DigitalMultiplierLimit = 11,
-    WhiteBalanceComponent = 12,
-    PowerlineFrequency = 13,
-}
-
-impl VideoProcAmpProperty {
-    fn as_str(self) -> &'static str {
-        match self {
-            Self::Brightness => "Brightness",
-            Self::Contrast => "Contrast",
-            Self::Hue => "Hue",
-            Self::Saturation => "Saturation",
-            Self::Sharpness => "Sharpness",
-            Self::Gamma => "Gamma",
-            Self::ColorEnable => "ColorEnable",
-            Self::WhiteBalance => "WhiteBalance",
-            Self::BacklightCompensation => "BacklightCompensation",
-            Self::Gain => "Gain",
-            Self::DigitalMultiplier => "DigitalMultiplier",
-            Self::DigitalMultiplierLimit => "DigitalMultiplierLimit",
-            Self::WhiteBalanceComponent => "WhiteBalanceComponent",
-            Self::PowerlineFrequency => "PowerlineFrequency",
-        }
-    }
-}
-
-impl fmt::Display for VideoProcAmpProperty {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str(self.as_str())
-    }
-}
-
-impl FromStr for VideoProcAmpProperty {
-    type Err = ();
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(match s {
-            "Brightness" => Self::Brightness,
-            "Contrast" => Self::Contrast,
-            "Hue" => Self::Hue,
-            "Saturation" => Self::Saturation,
-            "Sharpness" => Self::Sharpness,
-            "Gamma" => Self::Gamma,
-            "ColorEnable" => Self::ColorEnable,
-            "WhiteBalance" => Self::WhiteBalance,
-            "BacklightCompensation" => Self::BacklightCompensation,
-            "Gain" => Self::Gain,
-            "DigitalMultiplier" => Self::DigitalMultiplier,
-            "DigitalMultiplierLimit" => Self::DigitalMultiplierLimit,
-            "WhiteBalanceComponent" => Self::WhiteBalanceComponent,
-            "PowerlineFrequency" => Self::PowerlineFrequency,
-            _ => return Err(()),
-        })
-    }
-}
-
-impl From<VideoProcAmpProperty> for i32 {
-    fn from(property: VideoProcAmpProperty) -> Self {
-        property as i32
-    }
-}
-
-/// CameraControl property IDs from DirectShow (ksmedia.h)
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-#[repr(i32)]
-pub enum CameraControlProperty {
-    Pan = 0,
-    Tilt = 1,
-    Roll = 2,
-    Zoom = 3,
-    Exposure = 4,
-    Iris = 5,
-    Focus = 6,
-}
-
-impl CameraControlProperty {
-    fn as_str(self) -> &'static str {
-        match self {
-            Self::Pan => "Pan",
-            Self::Tilt => "Tilt",
-            Self::Roll => "Roll",
-            Self::Zoom => "Zoom",
-            Self::Exposure => "Exposure",
-            Self::Iris => "Iris",
-            Self::Focus => "Focus",
-        }
-    }
-}
-
-impl fmt::Display for CameraControlProperty {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str(self.as_str())
-    }
-}
-
-impl FromStr for CameraControlProperty {
-    type Err = ();
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(match s {
-            "Pan" => Self::Pan,
-            "Tilt" => Self::Tilt,
-            "Roll" => Self::Roll,
-            "Zoom" => Self::Zoom,
-            "Exposure" => Self::Exposure,
-            "Iris" => Self::Iris,
-            "Focus" => Self::Focus,
-            _ => return Err(()),
-        })
-    }
-}
-
-impl From<CameraControlProperty> for i32 {
-    fn from(property: CameraControlProperty) -> Self {
-        property as i32
-    }
-}
+/// Property flag bit for Auto mode (VideoProcAmp_Flags_Auto / CameraControl_Flags_Auto)
+pub const FLAGS_AUTO: i32 = 0x1;
+/// Property flag bit for Manual mode (VideoProcAmp_Flags_Manual / CameraControl_Flags_Manual)
+pub const FLAGS_MANUAL: i32 = 0x2;
 
 /// Property type enumeration
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -196,14 +35,104 @@ impl fmt::Display for PropertyType {
 }
 
 /// Device information including metadata and all available properties
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeviceInfo {
     pub name: Option<String>,
     pub device_path: Option<String>,
+    /// Where the device is plugged in, as reported by the driver, e.g. the V4L2 bus info
+    /// `usb-0000:00:14.0-1.3`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    #[serde(default)]
     pub video_proc_amp_properties: Vec<PropertyInfo>,
+    #[serde(default)]
     pub camera_control_properties: Vec<PropertyInfo>,
 }
 
+impl DeviceInfo {
+    /// Whether the device is real hardware or a software camera
+    pub fn kind(&self) -> DeviceKind {
+        DeviceKind::classify(
+            self.name.as_deref(),
+            self.device_path.as_deref(),
+            self.location.as_deref(),
+        )
+    }
+}
+
+/// Whether a capture device is real hardware or a software (virtual) camera
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeviceKind {
+    Physical,
+    Virtual,
+}
+
+// Friendly names of common virtual cameras (lowercase). A name matches when it starts
+// with one of these as whole words, e.g. "DroidCam Source 3" but not "Camouflage HD".
+const VIRTUAL_CAMERA_NAMES: &[&str] = &[
+    "obs virtual camera",
+    "obs-camera",
+    "nvidia broadcast",
+    "snap camera",
+    "manycam",
+    "xsplit vcam",
+    "mmhmm",
+    "logi capture",
+    "camo",
+    "reincubate camo",
+    "droidcam",
+    "iriun webcam",
+    "epoccam",
+    "dummy video device",
+];
+
+// Whether a friendly name is that of a known virtual camera, or says "virtual" as a word
+fn is_virtual_name(name: &str) -> bool {
+    let name = name.to_lowercase();
+    let words_end_at = |i: usize| !name[i..].starts_with(|c: char| c.is_alphanumeric());
+    VIRTUAL_CAMERA_NAMES
+        .iter()
+        .any(|n| name.starts_with(n) && words_end_at(n.len()))
+        || name
+            .split(|c: char| !c.is_alphanumeric())
+            .any(|word| word == "virtual")
+}
+
+impl DeviceKind {
+    /// Classify a device from its friendly name, device path and location. The bus is
+    /// trusted first: software and root-enumerated devices (and V4L2 loopback devices) are
+    /// virtual, USB and PCI devices are physical. Other devices are matched against the
+    /// names of known virtual cameras.
+    pub fn classify(name: Option<&str>, device_path: Option<&str>, location: Option<&str>) -> Self {
+        match device_path.map(DevicePath::parse).and_then(|p| p.bus) {
+            Some(Bus::Swd | Bus::Virtual) => return Self::Virtual,
+            Some(Bus::Usb | Bus::Pci) => return Self::Physical,
+            Some(Bus::Other(_)) | None => {}
+        }
+        // V4L2 bus info, e.g. `usb-0000:00:14.0-1.3` or `platform:v4l2loopback-000`
+        match location {
+            Some(l) if l.starts_with("usb-") || l.starts_with("pci-") => return Self::Physical,
+            Some(l) if l.starts_with("platform:v4l2loopback") => return Self::Virtual,
+            _ => {}
+        }
+        if name.is_some_and(is_virtual_name) {
+            Self::Virtual
+        } else {
+            Self::Physical
+        }
+    }
+}
+
+impl fmt::Display for DeviceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Physical => "physical",
+            Self::Virtual => "virtual",
+        })
+    }
+}
+
 /// Property information with current value, defaults, and supported values
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct PropertyInfo {
@@ -219,46 +148,61 @@ pub struct PropertyInfo {
     pub property_type: PropertyType,
 }
 
+impl PropertyInfo {
+    /// Registry entry for this property, if it is a known one
+    pub fn definition(&self) -> Option<&'static PropertyDef> {
+        registry::by_name(self.property_type, &self.name)
+    }
+}
+
 /// Return the current mode ("Auto" or "Manual") for a property given its
 /// current flags value and capability bits. Returns None when the property
 /// does not advertise Auto support, since the distinction is meaningless.
 pub fn current_mode(caps: i32, flags: i32) -> Option<&'static str> {
-    if caps & VideoProcAmp_Flags_Auto.0 == 0 {
+    if caps & FLAGS_AUTO == 0 {
         return None;
     }
-    if flags & VideoProcAmp_Flags_Auto.0 != 0 {
+    if flags & FLAGS_AUTO != 0 {
         Some("Auto")
     } else {
         Some("Manual")
     }
 }
 
-/// Returns the static value↔label table for enum-like properties.
-/// Each entry is (numeric_value, canonical_label).
-fn get_value_labels(property_name: &str) -> Option<&'static [(i32, &'static str)]> {
-    match property_name {
-        "PowerlineFrequency" => Some(&[(0, "Disabled"), (1, "50Hz"), (2, "60Hz"), (3, "Auto")]),
-        "ColorEnable" | "BacklightCompensation" => Some(&[(0, "Off"), (1, "On")]),
-        _ => None,
-    }
+// Value labels for enum-like properties, empty for numeric and unknown properties
+fn get_value_labels(property_name: &str) -> &'static [(i32, &'static str)] {
+    registry::lookup(property_name).map_or(&[], |p| p.labels)
 }
 
-/// Format a property value into a human-readable label based on the property name
+/// Format a property value for display: the label of enum-like values, or the value with
+/// its unit (e.g. `-6 (1/64s)` for Exposure, `10°` for Pan)
 pub fn format_property_value(property_name: &str, value: i32) -> String {
-    if let Some(labels) = get_value_labels(property_name) {
-        return labels
-            .iter()
-            .find(|&&(v, _)| v == value)
-            .map(|&(_, label)| label.to_string())
+    let Some(property) = registry::lookup(property_name) else {
+        return value.to_string();
+    };
+    if !property.labels.is_empty() {
+        return property
+            .label(value)
+            .map(str::to_string)
             .unwrap_or_else(|| format!("Unknown({})", value));
     }
-    value.to_string()
+    property
+        .unit
+        .format(value)
+        .unwrap_or_else(|| value.to_string())
+}
+
+/// Format a `PropertyValue` for display, adding units to numeric values
+pub fn format_value(property_name: &str, value: &PropertyValue) -> String {
+    match value {
+        PropertyValue::Manual(v) => format_property_value(property_name, *v),
+        other => other.to_string(),
+    }
 }
 
 /// Build enum mapping from property name and min/max for display
 pub fn build_enum_display(property_name: &str, min: i32, max: i32) -> Option<String> {
-    let labels = get_value_labels(property_name)?;
-    let display = labels
+    let display = get_value_labels(property_name)
         .iter()
         .filter(|&&(v, _)| v >= min && v <= max)
         .map(|&(val, label)| format!("{} ({})", label, val))
@@ -267,11 +211,188 @@ pub fn build_enum_display(property_name: &str, min: i32, max: i32) -> Option<Str
     Some(display).filter(|s| !s.is_empty())
 }
 
-/// Parse a value string to (value, auto_mode) tuple
-/// Handles both human-readable values (50Hz, On, Off, Auto) and numeric values
-/// Returns Ok((value, true)) if "Auto" is specified
-/// Returns Ok((parsed_value, false)) for other inputs
-pub fn parse_property_value(property_name: &str, value_str: &str) -> Result<(i32, bool)> {
+/// A property value as requested by the user or reported by a camera
+///
+/// Serializes as a JSON number for `Manual` values, as `{"label": "50Hz"}` for labels, and as
+/// a string for `Auto`, percentages and relative changes, so consumers get a typed value
+/// without re-parsing and a label that reads "Auto" is never mistaken for Auto mode.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyValue {
+    /// An explicit numeric value in Manual mode
+    Manual(i32),
+    /// Auto mode; the driver chooses the value
+    Auto,
+    /// A named value of an enum-like property (e.g. "50Hz", "On") or a value with a unit
+    /// (e.g. "1/60s", "15deg"), resolved against the property when written, in Manual mode
+    Label(String),
+    /// A position in the camera's min..max range (0-100), snapped to its step, in Manual mode
+    Percent(f64),
+    /// A change to the current value, clamped to the camera's range, in Manual mode
+    Relative(i32),
+    /// A change to the current value in multiples of the camera's step, clamped to the
+    /// camera's range, in Manual mode
+    RelativeSteps(i32),
+}
+
+impl PropertyValue {
+    /// Build a value from a raw reading, using the property's label when it has one
+    pub fn from_raw(property_name: &str, value: i32) -> Self {
+        registry::lookup(property_name)
+            .and_then(|p| p.label(value))
+            .map(|label| Self::Label(label.to_string()))
+            .unwrap_or(Self::Manual(value))
+    }
+
+    /// Raw numeric value to write for a property, resolving labels against the property
+    /// and percentages against its range. Returns Ok(None) for Auto, since the driver
+    /// chooses the value.
+    pub fn raw(&self, property: &PropertyInfo) -> Result<Option<i32>> {
+        match self {
+            Self::Manual(v) => Ok(Some(*v)),
+            Self::Auto => Ok(None),
+            Self::Label(label) => label_value(&property.name, label).map(Some),
+            Self::Percent(percent) => percent_value(property, *percent).map(Some),
+            Self::Relative(delta) => relative_value(property, i64::from(*delta)).map(Some),
+            Self::RelativeSteps(steps) => {
+                let step = i64::from(property.step.filter(|&s| s > 0).unwrap_or(1));
+                relative_value(property, i64::from(*steps) * step).map(Some)
+            }
+        }
+    }
+
+    /// Parse a relative change: a signed number (`+10`, `-2`) or a number of steps
+    /// (`-2step`, `+1steps`). Unlike `FromStr`, a leading `-` here means a decrease.
+    pub fn parse_delta(delta_str: &str) -> Result<Self> {
+        sanitize(delta_str)?;
+        let lower = delta_str.trim().to_ascii_lowercase();
+        let (number, steps) = match lower
+            .strip_suffix("steps")
+            .or_else(|| lower.strip_suffix("step"))
+        {
+            Some(number) => (number.trim(), true),
+            None => (lower.as_str(), false),
+        };
+        let delta = number.parse::<i32>().with_context(|| {
+            format!(
+                "Invalid relative value '{}'. Expected e.g. +10, -2 or -2step",
+                delta_str
+            )
+        })?;
+        Ok(if steps {
+            Self::RelativeSteps(delta)
+        } else {
+            Self::Relative(delta)
+        })
+    }
+
+    /// Express a raw value as a percentage of the property's range, rounded to one
+    /// decimal place. Returns None when the range is unknown or empty.
+    pub fn percent_of_range(property: &PropertyInfo, value: i32) -> Option<Self> {
+        let (min, max) = property.min.zip(property.max)?;
+        if max <= min {
+            return None;
+        }
+        let percent =
+            (f64::from(value) - f64::from(min)) / (f64::from(max) - f64::from(min)) * 100.0;
+        Some(Self::Percent((percent * 10.0).round() / 10.0))
+    }
+}
+
+impl fmt::Display for PropertyValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Manual(v) => write!(f, "{}", v),
+            Self::Auto => f.write_str("Auto"),
+            Self::Label(label) => f.write_str(label),
+            Self::Percent(percent) => write!(f, "{}%", percent),
+            Self::Relative(delta) => write!(f, "{:+}", delta),
+            Self::RelativeSteps(steps) => write!(f, "{:+}step", steps),
+        }
+    }
+}
+
+impl FromStr for PropertyValue {
+    type Err = anyhow::Error;
+
+    /// Parse a value string without reference to a property.
+    /// Numbers become `Manual`, "Auto" becomes `Auto`, "50%" becomes `Percent`, "+10" and
+    /// "-2step" are relative changes, and anything else is a `Label` that is checked against
+    /// the property's labels when it is written. A plain negative number is absolute; use
+    /// `parse_delta` to decrease by a raw amount.
+    fn from_str(value_str: &str) -> Result<Self> {
+        sanitize(value_str)?;
+
+        // Check if Auto mode is requested
+        if value_str.eq_ignore_ascii_case("auto") {
+            return Ok(Self::Auto);
+        }
+
+        // A leading '+' or a step count is always relative
+        let lower = value_str.to_ascii_lowercase();
+        if value_str.starts_with('+') || lower.ends_with("step") || lower.ends_with("steps") {
+            return Self::parse_delta(value_str);
+        }
+
+        if let Ok(v) = value_str.parse::<i32>() {
+            return Ok(Self::Manual(v));
+        }
+
+        if let Some(percent) = value_str.strip_suffix('%') {
+            let percent = percent
+                .trim()
+                .parse::<f64>()
+                .with_context(|| format!("Invalid percentage '{}'", value_str))?;
+            if !(0.0..=100.0).contains(&percent) {
+                anyhow::bail!("Percentage '{}' must be between 0% and 100%", value_str);
+            }
+            return Ok(Self::Percent(percent));
+        }
+
+        if value_str.is_empty() {
+            anyhow::bail!("Value is empty");
+        }
+        Ok(Self::Label(value_str.to_string()))
+    }
+}
+
+impl Serialize for PropertyValue {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Manual(v) => serializer.serialize_i32(*v),
+            Self::Label(label) => {
+                use serde::ser::SerializeMap;
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("label", label)?;
+                map.end()
+            }
+            other => serializer.collect_str(other),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for PropertyValue {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Number(i32),
+            Label { label: String },
+            Text(String),
+        }
+
+        match Repr::deserialize(deserializer)? {
+            Repr::Number(v) => Ok(Self::Manual(v)),
+            Repr::Label { label } => {
+                sanitize(&label).map_err(serde::de::Error::custom)?;
+                Ok(Self::Label(label))
+            }
+            Repr::Text(s) => s.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+// Sanitize a value string before it is parsed
+fn sanitize(value_str: &str) -> Result<()> {
     // Sanitize input: limit length to prevent potential issues
     if value_str.len() > 32 {
         anyhow::bail!("Value string exceeds maximum allowed length");
@@ -280,23 +401,24 @@ pub fn parse_property_value(property_name: &str, value_str: &str) -> Result<(i32
     // Sanitize input: only allow alphanumeric characters and specific safe characters
     if !value_str
         .chars()
-        .all(|c| c.is_alphanumeric() || c == '-' || c == ' ')
+        .all(|c| c.is_alphanumeric() || matches!(c, '-' | '+' | ' ' | '/' | '.' | '°' | '%'))
     {
         anyhow::bail!("Value contains invalid characters");
     }
 
-    // Check if Auto mode is requested
-    if value_str.eq_ignore_ascii_case("auto") {
-        return Ok((0, true)); // Value doesn't matter when auto is true
-    }
+    Ok(())
+}
+
+// Resolve a label (case-insensitive) or a value with a unit to its numeric value for a
+// property. Numeric strings are accepted too, so "1" works wherever "50Hz" does.
+fn label_value(property_name: &str, label: &str) -> Result<i32> {
+    let property = registry::lookup(property_name);
 
     // For enum-like properties: try label match first, then numeric parse
-    if let Some(labels) = get_value_labels(property_name) {
-        if let Some(&(v, _)) = labels
-            .iter()
-            .find(|&&(_, l)| l.eq_ignore_ascii_case(value_str))
-        {
-            return Ok((v, false));
+    let labels = get_value_labels(property_name);
+    if !labels.is_empty() {
+        if let Some(&(v, _)) = labels.iter().find(|&&(_, l)| l.eq_ignore_ascii_case(label)) {
+            return Ok(v);
         }
         // Numeric parse with a helpful hint listing valid labels
         let valid = labels
@@ -304,20 +426,79 @@ pub fn parse_property_value(property_name: &str, value_str: &str) -> Result<(i32
             .map(|&(_, l)| l)
             .collect::<Vec<_>>()
             .join(", ");
-        let v = value_str.parse::<i32>().with_context(|| {
+        return label.parse::<i32>().with_context(|| {
             format!(
                 "Invalid value '{}' for {}. Expected one of: {}, or a number",
-                value_str, property_name, valid
+                label, property_name, valid
             )
-        })?;
-        return Ok((v, false));
+        });
+    }
+
+    // Values with a unit, converted to the nearest raw value
+    if let Some(unit) = property.map(|p| p.unit)
+        && let Some(example) = unit.example()
+    {
+        if let Some(v) = unit.parse(label) {
+            return Ok(v);
+        }
+        return label.parse::<i32>().with_context(|| {
+            format!(
+                "Invalid value '{}' for {}. Expected a number or a value like {}",
+                label, property_name, example
+            )
+        });
     }
 
     // Generic numeric parse for non-enum properties
-    let v = value_str
+    label
         .parse::<i32>()
-        .with_context(|| format!("Invalid numeric value '{}'", value_str))?;
-    Ok((v, false))
+        .with_context(|| format!("Invalid numeric value '{}'", label))
+}
+
+// Map a percentage onto a property's min..max range, snapped to the nearest step
+fn percent_value(property: &PropertyInfo, percent: f64) -> Result<i32> {
+    let (Some(min), Some(max)) = (property.min, property.max) else {
+        anyhow::bail!(
+            "{} does not report a range, so percentages cannot be used",
+            property.name
+        );
+    };
+    let step = i64::from(property.step.filter(|&s| s > 0).unwrap_or(1));
+    let (min, max) = (i64::from(min), i64::from(max));
+
+    let target = (max - min) as f64 * percent / 100.0;
+    let mut raw = min + (target / step as f64).round() as i64 * step;
+    // The last step can overshoot max when the range is not a multiple of the step
+    if raw > max {
+        raw -= step;
+    }
+    Ok(raw.max(min).min(max) as i32)
+}
+
+// Apply a change to a property's current value, clamped to its range
+fn relative_value(property: &PropertyInfo, delta: i64) -> Result<i32> {
+    let current = property.current.with_context(|| {
+        format!(
+            "{} has no current value, so relative values cannot be used",
+            property.name
+        )
+    })?;
+    let min = property.min.map_or(i64::from(i32::MIN), i64::from);
+    let max = property.max.map_or(i64::from(i32::MAX), i64::from);
+    Ok((i64::from(current) + delta).max(min).min(max) as i32)
+}
+
+/// Parse a value string for a specific property
+/// Handles both human-readable values (50Hz, On, Off, Auto) and numeric values.
+/// Labels are checked against the property and returned in their canonical spelling.
+pub fn parse_property_value(property_name: &str, value_str: &str) -> Result<PropertyValue> {
+    match value_str.parse()? {
+        PropertyValue::Label(label) => {
+            let v = label_value(property_name, &label)?;
+            Ok(PropertyValue::from_raw(property_name, v))
+        }
+        value => Ok(value),
+    }
 }
 
 /// Simplified device list item for list command
@@ -325,206 +506,131 @@ pub fn parse_property_value(property_name: &str, value_str: &str) -> Result<(i32
 pub struct DeviceListItem {
     pub index: usize,
     pub name: String,
+    pub kind: DeviceKind,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub device_path: Option<String>,
+    /// Bus, USB IDs, serial and class parsed from the device path
+    #[serde(skip_serializing_if = "Option::is_none", skip_deserializing)]
+    pub hardware: Option<DevicePath>,
+    /// Names of the camera aliases pointing at this device
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
 }
 
 /// List all video capture devices (lightweight - names and paths only)
 /// This is a simplified version of enumerate_devices() for the list command
-#[instrument]
-pub fn list_devices() -> Result<Vec<DeviceListItem>> {
+#[instrument(skip(backend))]
+pub fn list_devices(backend: &dyn CameraBackend) -> Result<Vec<DeviceListItem>> {
     debug!("Listing video capture devices");
 
-    let devices = enumerate_devices()?;
+    let devices = backend.enumerate()?;
 
     Ok(devices
         .into_iter()
         .enumerate()
         .map(|(index, device)| DeviceListItem {
             index,
+            kind: device.kind(),
             name: device.name.unwrap_or_else(|| "Unknown".to_string()),
+            hardware: device
+                .device_path
+                .as_deref()
+                .map(DevicePath::parse)
+                .filter(|parsed| !parsed.is_empty()),
             device_path: device.device_path,
+            aliases: Vec::new(),
         })
         .collect())
 }
 
-/// Enumerate all video capture devices and return their information
-#[instrument]
-pub fn enumerate_devices() -> Result<Vec<DeviceInfo>> {
-    unsafe {
-        debug!("Initializing COM");
-        let _com = ComGuard::new()?;
-        debug!("COM initialized successfully");
-
-        debug!("Creating ICreateDevEnum");
-        // Create the System Device Enumerator
-        let dev_enum: ICreateDevEnum =
-            CoCreateInstance(&CLSID_SYSTEM_DEVICE_ENUM, None, CLSCTX_INPROC_SERVER)
-                .context("Failed to create device enumerator")?;
-        debug!("ICreateDevEnum created successfully");
-
-        debug!("Creating class enumerator for video input devices");
-        // Create an enumerator for the video input device category
-        let mut enum_moniker: Option<IEnumMoniker> = None;
-        let hr = dev_enum.CreateClassEnumerator(
-            &CLSID_VIDEO_INPUT_DEVICE_CATEGORY,
-            &mut enum_moniker,
-            0,
-        );
-        debug!("CreateClassEnumerator returned: {:?}", hr);
-        hr.context("Failed to create video device class enumerator")?;
-
-        let Some(enum_moniker) = enum_moniker else {
-            debug!("No video devices found (enum_moniker is None)");
-            return Ok(Vec::new());
-        };
-
-        let mut devices = Vec::new();
-
-        debug!("Starting device enumeration loop");
-        loop {
-            let mut monikers: [Option<IMoniker>; 1] = [None];
-            let mut fetched = 0u32;
-
-            trace!("Calling enum_moniker.Next()");
-            let hr = enum_moniker.Next(&mut monikers, Some(&mut fetched));
-
-            if hr != S_OK || fetched == 0 {
-                debug!("Enumeration complete. HR: {:?}, Fetched: {}", hr, fetched);
-                break;
-            }
-
-            trace!("Processing moniker {} (fetched={})", devices.len(), fetched);
-
-            if let Some(mon) = &monikers[0] {
-                trace!("Binding moniker to property bag");
-                let device_name = get_device_name(mon).ok();
-                debug!(device_name = ?device_name, "Processing device");
-
-                let mut device = DeviceInfo {
-                    name: device_name,
-                    device_path: None,
-                    video_proc_amp_properties: Vec::new(),
-                    camera_control_properties: Vec::new(),
-                };
-
-                // Get device path
-                trace!("Getting device path");
-                if let Ok(path) = get_device_path(mon) {
-                    trace!(device_path = %path, "Device path obtained");
-                    device.device_path = Some(path);
-                }
-
-                // Get VideoProcAmp properties
-                trace!("Querying VideoProcAmp properties");
-                if let Ok(props) = get_video_proc_amp_properties(mon) {
-                    debug!(
-                        property_count = props.len(),
-                        "VideoProcAmp properties enumerated"
-                    );
-                    device.video_proc_amp_properties = props;
-                } else {
-                    trace!("Failed to get VideoProcAmp properties");
-                }
-
-                // Get CameraControl properties
-                trace!("Querying CameraControl properties");
-                if let Ok(props) = get_camera_control_properties(mon) {
-                    debug!(
-                        property_count = props.len(),
-                        "CameraControl properties enumerated"
-                    );
-                    device.camera_control_properties = props;
-                } else {
-                    trace!("Failed to get CameraControl properties");
-                }
-
-                debug!(
-                    device_name = ?device.name,
-                    video_proc_amp_count = device.video_proc_amp_properties.len(),
-                    camera_control_count = device.camera_control_properties.len(),
-                    "Device enumeration complete"
-                );
-                devices.push(device);
+/// Labels telling apart devices that share a friendly name, such as `serial A1B2C3D4` or
+/// `port 1.3`, in the same order as `devices`. They come from the device itself rather than
+/// its position, so they survive replugging. Devices with a unique name get None; a device
+/// with nothing to tell it apart by falls back to its position among its namesakes (`#2`).
+pub fn discriminators<'a>(
+    devices: impl IntoIterator<Item = &'a DeviceInfo>,
+) -> Vec<Option<String>> {
+    let devices: Vec<&DeviceInfo> = devices.into_iter().collect();
+    devices
+        .iter()
+        .enumerate()
+        .map(|(index, device)| {
+            let twins: Vec<usize> = (0..devices.len())
+                .filter(|&i| devices[i].name == device.name)
+                .collect();
+            if twins.len() < 2 {
+                return None;
             }
-        }
-
-        Ok(devices)
-    }
+            discriminator(device).or_else(|| {
+                let number = twins.iter().position(|&i| i == index)? + 1;
+                Some(format!("#{}", number))
+            })
+        })
+        .collect()
 }
 
-#[instrument(skip(moniker))]
-fn get_device_name(moniker: &IMoniker) -> Result<String> {
-    debug!("Reading FriendlyName from property bag");
-    let result = get_property_string(moniker, "FriendlyName");
-    if let Ok(ref name) = result {
-        debug!(friendly_name = %name, "Device name obtained");
-    } else {
-        trace!("Failed to read FriendlyName");
+// Stable label for a device: its serial number or where it is plugged in
+fn discriminator(device: &DeviceInfo) -> Option<String> {
+    device
+        .device_path
+        .as_deref()
+        .map(DevicePath::parse)
+        .and_then(|parsed| parsed.discriminator())
+        .or_else(|| device.location.as_deref().map(location_label))
+}
+
+// Short form of a driver-reported location: the port path of USB bus info such as
+// `usb-0000:00:14.0-1.3` ("port 1.3"), or the whole location for other buses
+fn location_label(location: &str) -> String {
+    match location
+        .strip_prefix("usb-")
+        .and_then(|rest| rest.rsplit_once('-'))
+    {
+        Some((_, port)) => format!("port {}", port),
+        None => format!("location {}", location),
     }
-    result
 }
 
-#[instrument(skip(moniker))]
-fn get_device_path(moniker: &IMoniker) -> Result<String> {
-    debug!("Reading DevicePath from property bag");
-    let result = get_property_string(moniker, "DevicePath");
-    if let Ok(ref path) = result {
-        trace!(device_path = %path, "Device path obtained");
-    } else {
-        trace!("Failed to read DevicePath");
-    }
-    result
+/// Look up the canonical property name for an interface and numeric property ID
+pub fn property_name(property_type: PropertyType, property_id: i32) -> Option<&'static str> {
+    registry::by_id(property_type, property_id).map(|p| p.name)
 }
 
-#[instrument(skip(moniker))]
-fn get_property_string(moniker: &IMoniker, prop_name: &str) -> Result<String> {
-    trace!(property_name = %prop_name, "Binding moniker to property bag");
-    use windows::Win32::System::Variant::{VARIANT, VT_BSTR, VariantClear};
-    use windows::core::HSTRING;
-
-    // Bind to property bag (safe)
-    let prop_bag: IPropertyBag =
-        unsafe { moniker.BindToStorage(None, None) }.with_context(|| {
-            format!(
-                "Failed to bind to property bag for property '{}'",
-                prop_name
-            )
-        })?;
-    trace!("BindToStorage successful");
-
-    let mut var = VARIANT::default();
-    let prop_name_hstr = HSTRING::from(prop_name);
-
-    // Read property value (unsafe only for FFI call)
-    unsafe { prop_bag.Read(PCWSTR(prop_name_hstr.as_ptr()), &mut var, None) }
-        .with_context(|| format!("Failed to read property '{}'", prop_name))?;
-
-    // Extract the value (safe)
-    let result = if unsafe { var.Anonymous.Anonymous.vt } == VT_BSTR {
-        // Use windows::core::BSTR for conversion
-        let bstr = unsafe { &var.Anonymous.Anonymous.Anonymous.bstrVal };
-        let value = bstr.to_string();
-        trace!(property_value = %value, "Property value retrieved");
-        Ok(value)
-    } else {
-        Err(anyhow::anyhow!("Property '{}' is not a BSTR", prop_name))
-    };
-
-    // Always clear VARIANT regardless of success or failure (unsafe only for FFI call)
-    let _ = unsafe { VariantClear(&mut var) };
+/// Enumerate all video capture devices and return their information
+#[instrument(skip(backend))]
+pub fn enumerate_devices(backend: &dyn CameraBackend) -> Result<Vec<DeviceInfo>> {
+    let mut devices = backend.enumerate()?;
+
+    for device in &mut devices {
+        // Get VideoProcAmp properties
+        trace!("Querying VideoProcAmp properties");
+        device.video_proc_amp_properties =
+            get_properties(backend, device, PropertyType::VideoProcAmp);
+
+        // Get CameraControl properties
+        trace!("Querying CameraControl properties");
+        device.camera_control_properties =
+            get_properties(backend, device, PropertyType::CameraControl);
+
+        debug!(
+            device_name = ?device.name,
+            video_proc_amp_count = device.video_proc_amp_properties.len(),
+            camera_control_count = device.camera_control_properties.len(),
+            "Device enumeration complete"
+        );
+    }
 
-    result
+    Ok(devices)
 }
 
 // Convert capability flags to human-readable string ("Manual", "Auto", "Manual, Auto")
 fn format_capabilities(caps: i32) -> Option<String> {
     let mut cap_names = Vec::new();
 
-    if caps & VideoProcAmp_Flags_Manual.0 != 0 {
+    if caps & FLAGS_MANUAL != 0 {
         cap_names.push("Manual");
     }
-    if caps & VideoProcAmp_Flags_Auto.0 != 0 {
+    if caps & FLAGS_AUTO != 0 {
         cap_names.push("Auto");
     }
 
@@ -535,313 +641,286 @@ fn format_capabilities(caps: i32) -> Option<String> {
     }
 }
 
-fn get_properties<T, IFace, GetRangeFn, GetFn>(
-    moniker: &IMoniker,
-    iface_cast: fn(IBaseFilter) -> Result<IFace>,
-    properties: &[T],
-    get_range: GetRangeFn,
-    get_value: GetFn,
+// Query range and current value for each registered property of an interface; properties
+// whose GetRange fails are treated as unsupported and left out.
+fn get_properties(
+    backend: &dyn CameraBackend,
+    device: &DeviceInfo,
     property_type: PropertyType,
-) -> Result<Vec<PropertyInfo>>
-where
-    T: Copy + ToString + Into<i32>,
-    IFace: Clone,
-    GetRangeFn: Fn(
-        &IFace,
-        i32,
-        &mut i32,
-        &mut i32,
-        &mut i32,
-        &mut i32,
-        &mut i32,
-    ) -> windows::core::Result<()>,
-    GetFn: Fn(&IFace, i32, &mut i32, &mut i32) -> windows::core::Result<()>,
-{
-    debug!("Binding moniker to IBaseFilter");
-    let filter: IBaseFilter =
-        unsafe { moniker.BindToObject(None, None) }.context("Failed to bind to IBaseFilter")?;
-    let iface = iface_cast(filter)?;
-    debug!("Interface obtained");
-
+) -> Vec<PropertyInfo> {
     let mut capabilities = Vec::new();
-    trace!(property_count = properties.len(), "Enumerating properties");
-    for property in properties {
-        let prop_id: i32 = (*property).into();
-        let name = property.to_string();
-        let mut min = 0;
-        let mut max = 0;
-        let mut step = 0;
-        let mut default = 0;
-        let mut caps = 0;
-
-        if get_range(
-            &iface,
-            prop_id,
-            &mut min,
-            &mut max,
-            &mut step,
-            &mut default,
-            &mut caps,
-        )
-        .is_ok()
-        {
-            trace!(property = %name, min, max, step, default, caps, "GetRange successful");
-            let mut value = 0;
-            let mut flags_val = 0;
-            if get_value(&iface, prop_id, &mut value, &mut flags_val).is_ok() {
-                trace!(property = %name, value, flags = flags_val, "Get successful");
-            }
+    trace!(%property_type, "Enumerating properties");
+    for property in registry::of_type(property_type) {
+        let prop_id = property.id;
+        let name = property.name.to_string();
+
+        match backend.get_range(device, property_type, prop_id) {
+            Ok(range) => {
+                trace!(property = %name, ?range, "GetRange successful");
+                let (value, flags_val) = match backend.get(device, property_type, prop_id) {
+                    Ok((value, flags)) => {
+                        trace!(property = %name, value, flags, "Get successful");
+                        (value, flags)
+                    }
+                    Err(e) => {
+                        trace!(property = %name, error = %e, "Get failed");
+                        (0, 0)
+                    }
+                };
 
-            capabilities.push(PropertyInfo {
-                name: name.to_string(),
-                min: Some(min),
-                max: Some(max),
-                step: Some(step),
-                default: Some(default),
-                caps: Some(caps),
-                current: Some(value),
-                current_flags: Some(flags_val),
-                capabilities: format_capabilities(caps),
-                property_type,
-            });
-        } else {
-            trace!(property = %name, "GetRange failed - property not supported");
+                capabilities.push(PropertyInfo {
+                    name,
+                    min: Some(range.min),
+                    max: Some(range.max),
+                    step: Some(range.step),
+                    default: Some(range.default),
+                    caps: Some(range.caps),
+                    current: Some(value),
+                    current_flags: Some(flags_val),
+                    capabilities: format_capabilities(range.caps),
+                    property_type,
+                });
+            }
+            Err(e) => {
+                trace!(property = %name, error = %e, "GetRange failed - property not supported");
+            }
         }
     }
     debug!(
         property_count = capabilities.len(),
         "Property enumeration complete"
     );
-    Ok(capabilities)
-}
-
-unsafe fn get_video_proc_amp_properties(moniker: &IMoniker) -> Result<Vec<PropertyInfo>> {
-    get_properties(
-        moniker,
-        |f| f.cast().context("Failed to get IAMVideoProcAmp interface"),
-        &[
-            VideoProcAmpProperty::Brightness,
-            VideoProcAmpProperty::Contrast,
-            VideoProcAmpProperty::Saturation,
-            VideoProcAmpProperty::Hue,
-            VideoProcAmpProperty::WhiteBalance,
-            VideoProcAmpProperty::WhiteBalanceComponent,
-            VideoProcAmpProperty::ColorEnable,
-            VideoProcAmpProperty::Gamma,
-            VideoProcAmpProperty::Sharpness,
-            VideoProcAmpProperty::BacklightCompensation,
-            VideoProcAmpProperty::Gain,
-            VideoProcAmpProperty::PowerlineFrequency,
-            VideoProcAmpProperty::DigitalMultiplier,
-            VideoProcAmpProperty::DigitalMultiplierLimit,
-        ],
-        |iface: &IAMVideoProcAmp, prop_id, min, max, step, default, caps| unsafe {
-            iface.GetRange(prop_id, min, max, step, default, caps)
-        },
-        |iface: &IAMVideoProcAmp, prop_id, value, flags| unsafe {
-            iface.Get(prop_id, value, flags)
-        },
-        PropertyType::VideoProcAmp,
-    )
-}
-
-unsafe fn get_camera_control_properties(moniker: &IMoniker) -> Result<Vec<PropertyInfo>> {
-    get_properties(
-        moniker,
-        |f| f.cast().context("Failed to get IAMCameraControl interface"),
-        &[
-            CameraControlProperty::Exposure,
-            CameraControlProperty::Focus,
-            CameraControlProperty::Pan,
-            CameraControlProperty::Tilt,
-            CameraControlProperty::Roll,
-            CameraControlProperty::Zoom,
-            CameraControlProperty::Iris,
-        ],
-        |iface: &IAMCameraControl, prop_id, min, max, step, default, caps| unsafe {
-            iface.GetRange(prop_id, min, max, step, default, caps)
-        },
-        |iface: &IAMCameraControl, prop_id, value, flags| unsafe {
-            iface.Get(prop_id, value, flags)
-        },
-        PropertyType::CameraControl,
-    )
-}
-
-/// Find a device moniker by its DirectShow device path
-/// Used by set_property functions to locate the device for property modification
-unsafe fn find_device_by_path(target_path: &str) -> Result<IMoniker> {
-    unsafe {
-        let dev_enum: ICreateDevEnum =
-            CoCreateInstance(&CLSID_SYSTEM_DEVICE_ENUM, None, CLSCTX_INPROC_SERVER)?;
-
-        let mut enum_moniker: Option<IEnumMoniker> = None;
-        dev_enum.CreateClassEnumerator(&CLSID_VIDEO_INPUT_DEVICE_CATEGORY, &mut enum_moniker, 0)?;
-
-        let Some(enum_moniker) = enum_moniker else {
-            anyhow::bail!("No video devices found");
-        };
+    capabilities
+}
 
-        loop {
-            let mut monikers: [Option<IMoniker>; 1] = [None];
-            let mut fetched = 0u32;
+/// Set a property through the backend
+pub fn set_property(
+    backend: &dyn CameraBackend,
+    device: &DeviceInfo,
+    property: &PropertyDef,
+    value: i32,
+    auto: bool,
+) -> Result<()> {
+    backend
+        .set(device, property.property_type, property.id, value, auto)
+        .with_context(|| {
+            format!(
+                "Failed to set {} property {} to value {}",
+                property.property_type, property.name, value
+            )
+        })
+}
 
-            let hr = enum_moniker.Next(&mut monikers, Some(&mut fetched));
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            if hr != S_OK || fetched == 0 {
-                break;
-            }
+    #[test]
+    fn every_label_survives_a_json_round_trip() {
+        for property in registry::PROPERTIES {
+            for &(raw, label) in property.labels {
+                let value = PropertyValue::from_raw(property.name, raw);
+                assert_eq!(value, PropertyValue::Label(label.to_string()));
 
-            if let Some(mon) = monikers[0].take()
-                && let Ok(path) = get_device_path(&mon)
-                && path == target_path
-            {
-                return Ok(mon);
+                let json = serde_json::to_string(&value).unwrap();
+                let read: PropertyValue = serde_json::from_str(&json).unwrap();
+                assert_eq!(read, value, "{} {}", property.name, json);
+                assert_eq!(label_value(property.name, &read.to_string()).unwrap(), raw);
             }
         }
-
-        anyhow::bail!("Device not found")
     }
-}
-
-/// Shared boilerplate: initialize COM, find device, bind to IBaseFilter, invoke closure.
-/// The ComGuard lives for the duration of the closure so COM stays initialized.
-fn with_device_filter<R, F>(device: &DeviceInfo, f: F) -> Result<R>
-where
-    F: FnOnce(IBaseFilter) -> Result<R>,
-{
-    let _com = unsafe { ComGuard::new()? };
 
-    let target_path = device
-        .device_path
-        .as_ref()
-        .context("Device path not available")?;
+    #[test]
+    fn label_auto_is_not_auto_mode() {
+        let label = PropertyValue::from_raw("PowerlineFrequency", 3);
+        assert_eq!(
+            serde_json::to_value(&label).unwrap(),
+            serde_json::json!({ "label": "Auto" })
+        );
+        assert_eq!(
+            serde_json::to_value(PropertyValue::Auto).unwrap(),
+            serde_json::json!("Auto")
+        );
+        let read: PropertyValue = serde_json::from_str(r#""Auto""#).unwrap();
+        assert_eq!(read, PropertyValue::Auto);
+    }
 
-    let mon = unsafe { find_device_by_path(target_path)? };
-    let filter: IBaseFilter =
-        unsafe { mon.BindToObject(None, None) }.context("Failed to bind to device filter")?;
+    #[test]
+    fn other_values_round_trip() {
+        for value in [
+            PropertyValue::Manual(-6),
+            PropertyValue::Auto,
+            PropertyValue::Percent(37.5),
+            PropertyValue::Relative(10),
+            PropertyValue::RelativeSteps(-2),
+        ] {
+            let json = serde_json::to_string(&value).unwrap();
+            let read: PropertyValue = serde_json::from_str(&json).unwrap();
+            assert_eq!(read, value, "{}", json);
+        }
+    }
 
-    f(filter)
-}
+    #[test]
+    fn percent_maps_onto_range() {
+        let p = PropertyInfo::ranged("Brightness", 0, 255, 1);
+        assert_eq!(percent_value(&p, 0.0).unwrap(), 0);
+        assert_eq!(percent_value(&p, 50.0).unwrap(), 128);
+        assert_eq!(percent_value(&p, 100.0).unwrap(), 255);
 
-/// Set a VideoProcAmp property
-pub fn set_video_proc_amp_property(
-    device: &DeviceInfo,
-    property: VideoProcAmpProperty,
-    value: i32,
-    auto: bool,
-) -> Result<()> {
-    with_device_filter(device, |filter| {
-        let iface: IAMVideoProcAmp = filter
-            .cast()
-            .context("Failed to get VideoProcAmp interface")?;
-        let flags = if auto {
-            VideoProcAmp_Flags_Auto.0
-        } else {
-            VideoProcAmp_Flags_Manual.0
-        };
-        unsafe { iface.Set(property.into(), value, flags) }.with_context(|| {
-            format!(
-                "Failed to set VideoProcAmp property {} to value {}",
-                property, value
-            )
-        })
-    })
-}
+        let p = PropertyInfo::ranged("Brightness", -64, 64, 1);
+        assert_eq!(percent_value(&p, 50.0).unwrap(), 0);
+        assert_eq!(percent_value(&p, 25.0).unwrap(), -32);
+    }
 
-/// Set a CameraControl property
-pub fn set_camera_control_property(
-    device: &DeviceInfo,
-    property: CameraControlProperty,
-    value: i32,
-    auto: bool,
-) -> Result<()> {
-    with_device_filter(device, |filter| {
-        let iface: IAMCameraControl = filter
-            .cast()
-            .context("Failed to get CameraControl interface")?;
-        let flags = if auto {
-            CameraControl_Flags_Auto.0
-        } else {
-            CameraControl_Flags_Manual.0
-        };
-        unsafe { iface.Set(property.into(), value, flags) }.with_context(|| {
-            format!(
-                "Failed to set CameraControl property {} to value {}",
-                property, value
-            )
-        })
-    })
-}
+    #[test]
+    fn percent_snaps_to_step() {
+        let p = PropertyInfo::ranged("Brightness", 0, 250, 5);
+        assert_eq!(percent_value(&p, 33.0).unwrap(), 85);
+        // The top step would overshoot when the range isn't a multiple of the step
+        let p = PropertyInfo::ranged("Brightness", 0, 254, 5);
+        assert_eq!(percent_value(&p, 100.0).unwrap(), 250);
+        let p = PropertyInfo::ranged("Brightness", 2000, 6500, 10);
+        assert_eq!(percent_value(&p, 50.0).unwrap(), 4250);
+    }
 
-/// Set a property by name on a device
-/// High-level function that:
-/// - Parses the value string (handles Auto, 50Hz, On/Off, etc.)
-/// - Validates the property exists and value is within safe ranges
-/// - Determines if it's a VideoProcAmp or CameraControl property
-/// - Calls the appropriate low-level setter function
-pub fn set_property(device: &DeviceInfo, property_name: &str, value_str: &str) -> Result<()> {
-    // Sanitize property name - only allow alphanumeric characters
-    if !property_name.chars().all(|c| c.is_alphanumeric()) {
-        anyhow::bail!("Invalid property name: contains non-alphanumeric characters");
+    #[test]
+    fn percent_needs_a_range() {
+        let mut p = PropertyInfo::ranged("Brightness", 0, 255, 1);
+        p.max = None;
+        assert!(percent_value(&p, 50.0).is_err());
     }
 
-    // Limit property name length to prevent potential issues
-    if property_name.len() > 64 {
-        anyhow::bail!("Invalid property name: exceeds maximum length");
+    #[test]
+    fn percent_of_range_inverts_percent_value() {
+        let p = PropertyInfo::ranged("Brightness", 0, 200, 1);
+        assert_eq!(
+            PropertyValue::percent_of_range(&p, 50),
+            Some(PropertyValue::Percent(25.0))
+        );
+        assert_eq!(
+            PropertyValue::percent_of_range(&PropertyInfo::ranged("Brightness", 5, 5, 1), 5),
+            None
+        );
     }
 
-    // Parse the value string to get numeric value and auto flag
-    let (numeric_value, auto_mode) = parse_property_value(property_name, value_str)?;
+    #[test]
+    fn parse_percentages() {
+        assert_eq!(
+            "50%".parse::<PropertyValue>().unwrap(),
+            PropertyValue::Percent(50.0)
+        );
+        assert_eq!(
+            "12.5 %".parse::<PropertyValue>().unwrap(),
+            PropertyValue::Percent(12.5)
+        );
+        assert!("101%".parse::<PropertyValue>().is_err());
+        assert!("-1%".parse::<PropertyValue>().is_err());
+        assert!("abc%".parse::<PropertyValue>().is_err());
+    }
 
-    // Try to find the property in VideoProcAmp properties first
-    let property_info = device
-        .video_proc_amp_properties
-        .iter()
-        .find(|p| p.name.eq_ignore_ascii_case(property_name))
-        .map(|p| (p, PropertyType::VideoProcAmp))
-        .or_else(|| {
-            device
-                .camera_control_properties
-                .iter()
-                .find(|p| p.name.eq_ignore_ascii_case(property_name))
-                .map(|p| (p, PropertyType::CameraControl))
-        });
+    #[test]
+    fn discriminators_use_serial_or_port() {
+        let devices = [
+            DeviceInfo::named("Logitech BRIO").with_path(r"\\?\usb#vid_046d&pid_085e#6D1A2B3C#{x}"),
+            DeviceInfo::named("Razer Kiyo Pro").with_path("/dev/video2"),
+            DeviceInfo::named("Logitech BRIO")
+                .with_path("/dev/video4")
+                .with_location("usb-0000:00:14.0-1.3"),
+            DeviceInfo::named("Logitech BRIO")
+                .with_path("/dev/video6")
+                .with_location("platform:vivid-000"),
+        ];
+        assert_eq!(
+            discriminators(&devices),
+            [
+                Some("serial 6D1A2B3C".to_string()),
+                None,
+                Some("port 1.3".to_string()),
+                Some("location platform:vivid-000".to_string()),
+            ]
+        );
+    }
 
-    let (prop_info, property_type) = property_info.ok_or_else(|| {
-        anyhow::anyhow!(
-            "Property '{}' not found on device '{}'",
-            property_name,
-            device.name.as_deref().unwrap_or("Unknown")
-        )
-    })?;
+    #[test]
+    fn discriminators_do_not_depend_on_order() {
+        let brio = |port: &str| {
+            DeviceInfo::named("Logitech BRIO")
+                .with_path("/dev/video0")
+                .with_location(&format!("usb-0000:00:14.0-{}", port))
+        };
+        let forward = discriminators(&[brio("1"), brio("2")]);
+        let mut reversed = discriminators(&[brio("2"), brio("1")]);
+        reversed.reverse();
+        assert_eq!(forward, reversed);
+    }
 
-    // Validate value is within safe range (skip validation for auto mode)
-    if !auto_mode
-        && let (Some(min), Some(max)) = (prop_info.min, prop_info.max)
-        && (numeric_value < min || numeric_value > max)
-    {
-        anyhow::bail!(
-            "Value {} for property '{}' is outside the supported range [{}, {}]",
-            numeric_value,
-            property_name,
-            min,
-            max
+    #[test]
+    fn position_is_the_last_resort() {
+        let devices = [
+            DeviceInfo::named("Integrated Camera").with_path("/dev/video0"),
+            DeviceInfo::named("Integrated Camera"),
+        ];
+        assert_eq!(
+            discriminators(&devices),
+            [Some("#1".to_string()), Some("#2".to_string())]
         );
     }
 
-    match property_type {
-        PropertyType::VideoProcAmp => {
-            let prop_enum: VideoProcAmpProperty = property_name
-                .parse()
-                .map_err(|_| anyhow::anyhow!("Unknown VideoProcAmp property: {}", property_name))?;
-            set_video_proc_amp_property(device, prop_enum, numeric_value, auto_mode)
+    #[test]
+    fn known_virtual_names_match_whole_words() {
+        for name in [
+            "OBS Virtual Camera",
+            "DroidCam Source 3",
+            "Camo",
+            "NVIDIA Broadcast",
+            "Teams Virtual Camera",
+            "Dummy video device (0x0000)",
+        ] {
+            assert_eq!(
+                DeviceKind::classify(Some(name), None, None),
+                DeviceKind::Virtual,
+                "{}",
+                name
+            );
         }
-        PropertyType::CameraControl => {
-            let prop_enum: CameraControlProperty = property_name.parse().map_err(|_| {
-                anyhow::anyhow!("Unknown CameraControl property: {}", property_name)
-            })?;
-            set_camera_control_property(device, prop_enum, numeric_value, auto_mode)
+        for name in [
+            "Microsoft Teams Room Camera",
+            "Camouflage HD",
+            "Virtually Invisible Webcam",
+            "HD Pro Webcam C920",
+        ] {
+            assert_eq!(
+                DeviceKind::classify(Some(name), None, None),
+                DeviceKind::Physical,
+                "{}",
+                name
+            );
         }
     }
+
+    #[test]
+    fn bus_beats_the_name() {
+        // A real USB camera whose name looks like a virtual one
+        let path = r"\\?\usb#vid_045e&pid_0990&mi_00#7&1c3f2a4b&0&0000#{x}";
+        assert_eq!(
+            DeviceKind::classify(Some("Camo Studio Cam"), Some(path), None),
+            DeviceKind::Physical
+        );
+        assert_eq!(
+            DeviceKind::classify(
+                Some("DroidCam Video"),
+                Some("/dev/video0"),
+                Some("usb-0000:00:14.0-1.3")
+            ),
+            DeviceKind::Physical
+        );
+        assert_eq!(
+            DeviceKind::classify(
+                Some("Loopback"),
+                Some("/dev/video9"),
+                Some("platform:v4l2loopback-000")
+            ),
+            DeviceKind::Virtual
+        );
+    }
 }
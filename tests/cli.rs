@@ -0,0 +1,71 @@
+//! End-to-end runs of the command-line tool against the sample fixture
+use std::process::{Command, Output};
+
+const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/sample-cameras.json");
+
+// Run wincamcfg with a throwaway config directory, so no user state is read or written
+fn wincamcfg(args: &[&str]) -> Output {
+    let config = std::env::temp_dir().join(format!("wincamcfg-cli-{}", std::process::id()));
+    Command::new(env!("CARGO_BIN_EXE_wincamcfg"))
+        .args(args)
+        .env("XDG_CONFIG_HOME", &config)
+        .env("APPDATA", &config)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn check_that_cannot_run_exits_2() {
+    let missing = std::env::temp_dir().join("wincamcfg-missing-fixture.json");
+    let profile = std::env::temp_dir().join("wincamcfg-missing-profile.json");
+    let output = wincamcfg(&[
+        "--fixture",
+        missing.to_str().unwrap(),
+        "check",
+        "--profile",
+        profile.to_str().unwrap(),
+    ]);
+    assert_eq!(output.status.code(), Some(2));
+    let text = stdout(&output);
+    assert!(
+        text.starts_with("Error: Failed to read fixture file"),
+        "{}",
+        text
+    );
+    assert_eq!(text.lines().count(), 1);
+
+    // Same for a profile that can't be read
+    let output = wincamcfg(&[
+        "--fixture",
+        FIXTURE,
+        "check",
+        "--profile",
+        profile.to_str().unwrap(),
+    ]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stdout(&output).starts_with("Error: Failed to read profile"));
+}
+
+#[test]
+fn exported_profile_is_compliant() {
+    let profile = std::env::temp_dir().join(format!("wincamcfg-cli-{}.json", std::process::id()));
+    let profile = profile.to_str().unwrap();
+    let output = wincamcfg(&[
+        "--fixture",
+        FIXTURE,
+        "export",
+        "--camera",
+        "0",
+        "--file",
+        profile,
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let output = wincamcfg(&["--fixture", FIXTURE, "check", "--profile", profile]);
+    assert_eq!(output.status.code(), Some(0), "{}", stdout(&output));
+    std::fs::remove_file(profile).unwrap();
+}